@@ -3,21 +3,27 @@ use std::{
     collections::{BinaryHeap, HashMap},
 };
 
-use crate::{types::NodeId, Graph};
+use crate::{
+    graph_ref::GraphRef,
+    types::{EdgeId, NodeId, Weight},
+    Graph,
+};
 
 /// State represents a node in the path-finding process
-/// cost: the total cost to reach this node
+/// cost: the cost to reach this node plus the estimate to the end
 /// position: the current node's ID
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, PartialEq)]
 struct State {
-    cost: u32,
+    cost: Weight,
     position: NodeId,
 }
 
+impl Eq for State {}
+
 // Custom ordering for State to create a min-heap based on cost
 impl Ord for State {
     fn cmp(&self, other: &Self) -> Ordering {
-        other.cost.cmp(&self.cost)
+        other.cost.total_cmp(&self.cost)
     }
 }
 
@@ -27,40 +33,88 @@ impl PartialOrd for State {
     }
 }
 
-pub fn a_search(graph: &Graph, start: NodeId, end: NodeId) -> Option<Vec<NodeId>> {
+pub fn a_search<G: GraphRef>(graph: &G, start: NodeId, end: NodeId) -> Option<Vec<NodeId>> {
+    a_star(graph, start, end, |_| 0.0).map(|(path, _)| path)
+}
+
+/// Like [`a_search`], but returns the IDs of the edges along the path,
+/// telling apart parallel edges between the same nodes
+pub fn a_search_edges<G: GraphRef>(graph: &G, start: NodeId, end: NodeId) -> Option<Vec<EdgeId>> {
+    a_star(graph, start, end, |_| 0.0).map(|(_, edges)| edges)
+}
+
+/// Like [`a_search`], guided by `heuristic`, an estimate of the cost from a
+/// node to `end` that must never exceed the actual cost
+pub fn a_search_with_heuristic<G: GraphRef>(
+    graph: &G,
+    start: NodeId,
+    end: NodeId,
+    heuristic: impl Fn(NodeId) -> Weight,
+) -> Option<Vec<NodeId>> {
+    a_star(graph, start, end, heuristic).map(|(path, _)| path)
+}
+
+/// Like [`a_search`], estimating the cost to `end` by the straight-line
+/// distance between node positions, so edge weights must be at least the
+/// distance between their ends. Nodes without a position are estimated at zero.
+pub fn a_search_by_position(graph: &Graph, start: NodeId, end: NodeId) -> Option<Vec<NodeId>> {
+    let position = |node| graph.node_position_by_id(node).ok().flatten();
+    let end_position = position(end);
+    let heuristic = |node| match (position(node), end_position) {
+        (Some(position), Some(end_position)) => position.distance(&end_position),
+        _ => 0.0,
+    };
+    a_star(graph, start, end, heuristic).map(|(path, _)| path)
+}
+
+/// Returns the nodes and edges of the path
+fn a_star<G: GraphRef>(
+    graph: &G,
+    start: NodeId,
+    end: NodeId,
+    heuristic: impl Fn(NodeId) -> Weight,
+) -> Option<(Vec<NodeId>, Vec<EdgeId>)> {
     // Priority queue for the open set
     let mut open_set = BinaryHeap::new();
     // Map to track the cost of reaching each node
     let mut g_score = HashMap::new();
-    // Map to track the best path to each node
+    // Map to track the best path (previous node and edge) to each node
     let mut came_from = HashMap::new();
 
     // Initialize the starting node
-    g_score.insert(start, 0);
-    open_set.push(State { cost: 0, position: start });
+    g_score.insert(start, 0.0);
+    open_set.push(State { cost: heuristic(start), position: start });
 
     while let Some(State { cost: _, position }) = open_set.pop() {
         // If we reached the end, reconstruct and return the path
         if position == end {
             let mut path = vec![end];
+            let mut edges = Vec::new();
             let mut current = end;
-            while let Some(&previous) = came_from.get(&current) {
+            while let Some(&(previous, edge)) = came_from.get(&current) {
                 path.push(previous);
+                edges.push(edge);
                 current = previous;
             }
             path.reverse();
-            return Some(path);
+            edges.reverse();
+            return Some((path, edges));
         }
 
         // Explore neighbors
-        for (neighbor, weight) in graph.neighbors_with_weights(position).unwrap() {
-            let tentative_g_score = g_score[&position] + weight as u32;
+        for edge in graph.outgoing_edges(position) {
+            // Edges skipped by the missing weight policy are not followed
+            let Some(weight) = edge.weight else {
+                continue;
+            };
+            let tentative_g_score = g_score[&position] + weight;
 
             // If this path is better, record it
-            if tentative_g_score < *g_score.get(&neighbor).unwrap_or(&u32::MAX) {
-                came_from.insert(neighbor, position);
-                g_score.insert(neighbor, tentative_g_score);
-                open_set.push(State { cost: tentative_g_score, position: neighbor });
+            if tentative_g_score < *g_score.get(&edge.target).unwrap_or(&Weight::INFINITY) {
+                came_from.insert(edge.target, (position, edge.id));
+                g_score.insert(edge.target, tentative_g_score);
+                let cost = tentative_g_score + heuristic(edge.target);
+                open_set.push(State { cost, position: edge.target });
             }
         }
     }
@@ -71,7 +125,7 @@ pub fn a_search(graph: &Graph, start: NodeId, end: NodeId) -> Option<Vec<NodeId>
 
 #[cfg(test)]
 mod tests {
-    use crate::edge::Edge;
+    use crate::{edge::Edge, node::Node};
 
     use super::*;
 
@@ -101,4 +155,64 @@ mod tests {
         let path = a_search(&graph, 1, 5);
         assert_eq!(path, None);
     }
+
+    #[test]
+    fn test_a_search_edges() {
+        let edges = vec![
+            Edge::builder().source_id(1).target_id(2).weight(5.0).build().unwrap(),
+            Edge::builder().source_id(1).target_id(2).weight(1.0).build().unwrap(),
+            Edge::builder().source_id(2).target_id(3).weight(2.0).build().unwrap(),
+        ];
+        let graph = Graph::builder().edges(edges).build().unwrap();
+        assert_eq!(a_search_edges(&graph, 1, 3), Some(vec![1, 2]));
+        assert_eq!(a_search_edges(&graph, 3, 1), None);
+    }
+
+    #[test]
+    fn test_a_search_by_position() {
+        // A 3x3 grid with unit spacing, where the diagonal shortcut is longer.
+        let nodes = (0..9)
+            .map(|id| {
+                let position = ((id % 3) as f64, (id / 3) as f64);
+                Node::builder().id(id).position(position).build().unwrap()
+            })
+            .collect::<Vec<_>>();
+        let mut edges = Vec::new();
+        for id in 0..9 {
+            if id % 3 < 2 {
+                edges.push(Edge::builder().source_id(id).target_id(id + 1).weight(1.0).build());
+            }
+            if id < 6 {
+                edges.push(Edge::builder().source_id(id).target_id(id + 3).weight(1.0).build());
+            }
+        }
+        edges.push(Edge::builder().source_id(0).target_id(8).weight(5.0).build());
+        let edges = edges.into_iter().map(Result::unwrap).collect::<Vec<_>>();
+        let graph = Graph::builder().nodes(nodes).edges(edges).build().unwrap();
+
+        let path = a_search_by_position(&graph, 0, 8).unwrap();
+        assert_eq!(path.len(), 5);
+        assert_eq!(path.first(), Some(&0));
+        assert_eq!(path.last(), Some(&8));
+        assert_eq!(a_search_with_heuristic(&graph, 0, 8, |_| 0.0).unwrap().len(), 5);
+        assert_eq!(a_search_by_position(&graph, 8, 0), None);
+    }
+
+    #[test]
+    fn test_fractional_weights() {
+        // Two light edges sum to more than the direct edge.
+        let nodes = vec![
+            Node::builder().id(1).position((0.0, 0.0)).build().unwrap(),
+            Node::builder().id(2).position((0.7, 0.0)).build().unwrap(),
+            Node::builder().id(3).position((1.5, 0.0)).build().unwrap(),
+        ];
+        let edges = vec![
+            Edge::builder().source_id(1).target_id(2).weight(0.9).build().unwrap(),
+            Edge::builder().source_id(2).target_id(3).weight(0.9).build().unwrap(),
+            Edge::builder().source_id(1).target_id(3).weight(1.5).build().unwrap(),
+        ];
+        let graph = Graph::builder().nodes(nodes).edges(edges).build().unwrap();
+        assert_eq!(a_search(&graph, 1, 3), Some(vec![1, 3]));
+        assert_eq!(a_search_by_position(&graph, 1, 3), Some(vec![1, 3]));
+    }
 }
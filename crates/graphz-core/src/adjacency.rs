@@ -0,0 +1,116 @@
+use std::ops::Range;
+
+use crate::types::{EdgeId, NodeId, Weight};
+
+/// Compressed sparse row (CSR) index over the edges of a graph.
+///
+/// Entries are grouped by their key node, so the adjacent nodes of a key are
+/// a contiguous range of `targets`, `weights`, `edges` and `edge_ids`. Keys are kept
+/// sorted, which makes a lookup a binary search instead of an edge scan.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AdjacencyIndex {
+    nodes: Vec<NodeId>,
+    offsets: Vec<usize>,
+    targets: Vec<NodeId>,
+    weights: Vec<Option<Weight>>,
+    edges: Vec<usize>,
+    edge_ids: Vec<EdgeId>,
+}
+
+impl AdjacencyIndex {
+    /// Builds the index from `(key, target, weight, edge row, edge id)` entries.
+    pub fn from_entries(
+        entries: impl IntoIterator<Item = (NodeId, NodeId, Option<Weight>, usize, EdgeId)>,
+    ) -> Self {
+        let mut entries = entries.into_iter().collect::<Vec<_>>();
+        // Sorting by edge row as well keeps neighbors in insertion order.
+        entries.sort_unstable_by_key(|&(key, _, _, edge, _)| (key, edge));
+
+        let mut index = Self {
+            nodes: Vec::new(),
+            offsets: Vec::new(),
+            targets: Vec::with_capacity(entries.len()),
+            weights: Vec::with_capacity(entries.len()),
+            edges: Vec::with_capacity(entries.len()),
+            edge_ids: Vec::with_capacity(entries.len()),
+        };
+        for (key, target, weight, edge, edge_id) in entries {
+            if index.nodes.last() != Some(&key) {
+                index.nodes.push(key);
+                index.offsets.push(index.targets.len());
+            }
+            index.targets.push(target);
+            index.weights.push(weight);
+            index.edges.push(edge);
+            index.edge_ids.push(edge_id);
+        }
+        index.offsets.push(index.targets.len());
+        index
+    }
+
+    /// Number of indexed entries.
+    pub fn len(&self) -> usize {
+        self.targets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    fn range(&self, node: NodeId) -> Range<usize> {
+        match self.nodes.binary_search(&node) {
+            Ok(pos) => self.offsets[pos]..self.offsets[pos + 1],
+            Err(_) => 0..0,
+        }
+    }
+
+    /// Nodes adjacent to `node`.
+    pub fn neighbors(&self, node: NodeId) -> &[NodeId] {
+        &self.targets[self.range(node)]
+    }
+
+    /// Weights of the edges to the nodes returned by [`Self::neighbors`].
+    ///
+    /// Edges left out of weighted algorithms by [`crate::MissingWeight::Skip`]
+    /// have no weight.
+    pub fn weights(&self, node: NodeId) -> &[Option<Weight>] {
+        &self.weights[self.range(node)]
+    }
+
+    /// Edge rows of the edges to the nodes returned by [`Self::neighbors`].
+    pub fn edges(&self, node: NodeId) -> &[usize] {
+        &self.edges[self.range(node)]
+    }
+
+    /// Ids of the edges to the nodes returned by [`Self::neighbors`].
+    pub fn edge_ids(&self, node: NodeId) -> &[EdgeId] {
+        &self.edge_ids[self.range(node)]
+    }
+
+    pub fn degree(&self, node: NodeId) -> usize {
+        self.range(node).len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjacency_index() {
+        let index = AdjacencyIndex::from_entries(vec![
+            (3, 1, Some(1.0), 0, 10),
+            (1, 2, Some(2.0), 1, 11),
+            (1, 3, None, 2, 12),
+            (2, 3, Some(4.0), 3, 13),
+        ]);
+        assert_eq!(index.len(), 4);
+        assert_eq!(index.neighbors(1), &[2, 3]);
+        assert_eq!(index.weights(1), &[Some(2.0), None]);
+        assert_eq!(index.edges(1), &[1, 2]);
+        assert_eq!(index.edge_ids(1), &[11, 12]);
+        assert_eq!(index.neighbors(3), &[1]);
+        assert_eq!(index.degree(2), 1);
+        assert!(index.neighbors(4).is_empty());
+    }
+}
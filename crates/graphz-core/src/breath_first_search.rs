@@ -1,8 +1,12 @@
-use crate::{types::NodeId, Graph};
+use crate::{graph_ref::GraphRef, types::NodeId};
 use std::collections::{HashSet, VecDeque};
 use tracing::{debug, info};
 
-pub fn breath_first_search(graph: &Graph, start: NodeId, end: NodeId) -> Option<Vec<NodeId>> {
+pub fn breath_first_search<G: GraphRef>(
+    graph: &G,
+    start: NodeId,
+    end: NodeId,
+) -> Option<Vec<NodeId>> {
     info!("Starting BFS from node {} to node {}", start, end);
     let mut visited = HashSet::new();
     let mut queue = VecDeque::new();
@@ -20,13 +24,11 @@ pub fn breath_first_search(graph: &Graph, start: NodeId, end: NodeId) -> Option<
             debug!("Node {} already visited, skipping", current);
             continue;
         }
-        for idx in 0..graph.num_edges() {
-            if graph.source_id(idx).unwrap() == current {
-                let mut new_path = current_path.clone();
-                new_path.push(graph.target_id(idx).unwrap());
-                info!("Found path to node {}: {:?}", graph.target_id(idx).unwrap(), new_path);
-                queue.push_back((graph.target_id(idx).unwrap(), new_path));
-            }
+        for neighbor in graph.neighbors(current) {
+            let mut new_path = current_path.clone();
+            new_path.push(neighbor);
+            info!("Found path to node {}: {:?}", neighbor, new_path);
+            queue.push_back((neighbor, new_path));
         }
 
         debug!("Current queue state: {:?}", queue);
@@ -39,7 +41,7 @@ pub fn breath_first_search(graph: &Graph, start: NodeId, end: NodeId) -> Option<
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{edge::Edge, Graph};
+    use crate::{edge::Edge, Graph, GraphKind};
 
     #[test]
     fn test_bfs() {
@@ -54,4 +56,18 @@ mod tests {
         let path = breath_first_search(&graph, 1, 3);
         assert_eq!(path, Some(vec![1, 2, 3]));
     }
+
+    #[test]
+    fn test_bfs_undirected() {
+        let edges = vec![
+            Edge::builder().source_id(1).target_id(2).build().unwrap(),
+            Edge::builder().source_id(2).target_id(3).build().unwrap(),
+            Edge::builder().source_id(3).target_id(4).build().unwrap(),
+        ];
+        let graph = Graph::builder().edges(edges.clone()).build().unwrap();
+        assert_eq!(breath_first_search(&graph, 4, 2), None);
+
+        let graph = Graph::builder().edges(edges).kind(GraphKind::Undirected).build().unwrap();
+        assert_eq!(breath_first_search(&graph, 4, 2), Some(vec![4, 3, 2]));
+    }
 }
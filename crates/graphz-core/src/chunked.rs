@@ -0,0 +1,316 @@
+use arrow::{
+    array::{Array, ArrayRef, BooleanArray, PrimitiveArray, RecordBatch},
+    compute::{concat_batches, filter_record_batch},
+    datatypes::{ArrowPrimitiveType, Schema, SchemaRef},
+    error::ArrowError,
+};
+use std::sync::Arc;
+
+use crate::property::conform_to_schema;
+
+/// Record batches sharing one schema, read as a single table.
+///
+/// Appending adds a chunk instead of copying the existing rows. Rows are
+/// numbered across the chunks in order.
+#[derive(Debug, Clone)]
+pub(crate) struct ChunkedRecordBatch {
+    schema: SchemaRef,
+    chunks: Vec<RecordBatch>,
+    // Row at which each chunk starts
+    offsets: Vec<usize>,
+    num_rows: usize,
+}
+
+impl ChunkedRecordBatch {
+    /// Builds a table from chunks with the fields of `schema`. The chunks
+    /// take over the metadata of `schema`, and empty chunks are dropped.
+    pub fn try_new(schema: SchemaRef, chunks: Vec<RecordBatch>) -> Result<Self, ArrowError> {
+        let mut record_batch =
+            Self { schema, chunks: Vec::new(), offsets: Vec::new(), num_rows: 0 };
+        for chunk in chunks {
+            if chunk.schema_ref().fields() != record_batch.schema.fields() {
+                return Err(ArrowError::SchemaError(format!(
+                    "chunk schema {} does not match {}",
+                    chunk.schema_ref(),
+                    record_batch.schema
+                )));
+            }
+            let chunk =
+                RecordBatch::try_new(record_batch.schema.clone(), chunk.columns().to_vec())?;
+            record_batch.push_chunk(chunk);
+        }
+        Ok(record_batch)
+    }
+
+    fn push_chunk(&mut self, chunk: RecordBatch) {
+        if chunk.num_rows() == 0 {
+            return;
+        }
+        self.offsets.push(self.num_rows);
+        self.num_rows += chunk.num_rows();
+        self.chunks.push(chunk);
+    }
+
+    pub fn schema_ref(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    pub fn chunks(&self) -> &[RecordBatch] {
+        &self.chunks
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    /// Returns the column at `idx` of every chunk, or `None` if it is not a `T`.
+    pub fn column<T: Array + 'static>(&self, idx: usize) -> Option<ChunkedColumn<'_, T>> {
+        let chunks = self
+            .chunks
+            .iter()
+            .map(|chunk| chunk.column(idx).as_any().downcast_ref::<T>())
+            .collect::<Option<Vec<_>>>()?;
+        Some(ChunkedColumn { chunks, offsets: &self.offsets })
+    }
+
+    /// Appends the rows of `chunk` without copying the existing rows. Columns
+    /// only in `chunk` are added as nullable columns, and columns missing on
+    /// either side are null.
+    pub fn push(&mut self, chunk: RecordBatch) -> Result<(), ArrowError> {
+        let new_fields = chunk
+            .schema_ref()
+            .fields()
+            .iter()
+            .filter(|field| self.schema.field_with_name(field.name()).is_err())
+            .map(|field| Arc::new(field.as_ref().clone().with_nullable(true)))
+            .collect::<Vec<_>>();
+        if !new_fields.is_empty() {
+            let mut fields = self.schema.fields().to_vec();
+            fields.extend(new_fields);
+            let schema =
+                Arc::new(Schema::new_with_metadata(fields, self.schema.metadata().clone()));
+            self.chunks = self
+                .chunks
+                .iter()
+                .map(|chunk| conform_to_schema(chunk, schema.clone()))
+                .collect::<Result<_, _>>()?;
+            self.schema = schema;
+        }
+        let chunk = conform_to_schema(&chunk, self.schema.clone())?;
+        self.push_chunk(chunk);
+        Ok(())
+    }
+
+    /// Replaces the column at `idx` with one array per chunk.
+    pub fn replace_column(&mut self, idx: usize, columns: Vec<ArrayRef>) -> Result<(), ArrowError> {
+        self.chunks = self
+            .chunks
+            .iter()
+            .zip(columns)
+            .map(|(chunk, column)| {
+                let mut columns = chunk.columns().to_vec();
+                columns[idx] = column;
+                RecordBatch::try_new(self.schema.clone(), columns)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(())
+    }
+
+    /// Keeps the rows where `keep` is true.
+    pub fn filter(&self, keep: &BooleanArray) -> Result<Self, ArrowError> {
+        let chunks = self
+            .chunks
+            .iter()
+            .zip(&self.offsets)
+            .map(|(chunk, offset)| {
+                filter_record_batch(chunk, &keep.slice(*offset, chunk.num_rows()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::try_new(self.schema.clone(), chunks)
+    }
+
+    pub fn with_metadata(self, key: &str, value: String) -> Self {
+        let mut metadata = self.schema.metadata().clone();
+        metadata.insert(key.to_string(), value);
+        let schema = Arc::new(self.schema.as_ref().clone().with_metadata(metadata));
+        let chunks = self
+            .chunks
+            .iter()
+            .map(|chunk| RecordBatch::try_new(schema.clone(), chunk.columns().to_vec()).unwrap())
+            .collect();
+        Self { schema, chunks, ..self }
+    }
+
+    /// Returns the rows as one record batch, concatenating the chunks if
+    /// there is more than one.
+    pub fn to_record_batch(&self) -> RecordBatch {
+        match self.chunks.as_slice() {
+            [chunk] => chunk.clone(),
+            // The chunks share the schema, so they always concatenate.
+            chunks => concat_batches(&self.schema, chunks).unwrap(),
+        }
+    }
+
+    /// Merges the chunks into one.
+    pub fn compact(&mut self) {
+        if self.chunks.len() > 1 {
+            *self = Self::from(self.to_record_batch());
+        }
+    }
+}
+
+impl PartialEq for ChunkedRecordBatch {
+    /// Tables are equal if they hold the same rows, however they are chunked.
+    fn eq(&self, other: &Self) -> bool {
+        self.schema == other.schema
+            && self.num_rows == other.num_rows
+            && self.to_record_batch() == other.to_record_batch()
+    }
+}
+
+impl From<RecordBatch> for ChunkedRecordBatch {
+    fn from(record_batch: RecordBatch) -> Self {
+        let mut chunked = Self {
+            schema: record_batch.schema(),
+            chunks: Vec::new(),
+            offsets: Vec::new(),
+            num_rows: 0,
+        };
+        chunked.push_chunk(record_batch);
+        chunked
+    }
+}
+
+/// A column of a chunked record batch, one array per chunk.
+#[derive(Debug, Clone)]
+pub struct ChunkedColumn<'a, T> {
+    chunks: Vec<&'a T>,
+    offsets: &'a [usize],
+}
+
+impl<'a, T: Array> ChunkedColumn<'a, T> {
+    pub fn chunks(&self) -> &[&'a T] {
+        &self.chunks
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the array holding the row `idx` and the index of the row in it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn chunk_at(&self, idx: usize) -> (&'a T, usize) {
+        let (chunk, idx) = self.locate(idx);
+        (self.chunks[chunk], idx)
+    }
+
+    /// Like [`Self::chunk_at`], returning the position of the array in
+    /// [`Self::chunks`] instead of the array.
+    pub fn locate(&self, idx: usize) -> (usize, usize) {
+        let chunk = self.offsets.partition_point(|offset| *offset <= idx) - 1;
+        (chunk, idx - self.offsets[chunk])
+    }
+
+    pub fn is_valid(&self, idx: usize) -> bool {
+        let (chunk, idx) = self.chunk_at(idx);
+        chunk.is_valid(idx)
+    }
+}
+
+impl<'a, N: ArrowPrimitiveType> ChunkedColumn<'a, PrimitiveArray<N>> {
+    pub fn value(&self, idx: usize) -> N::Native {
+        let (chunk, idx) = self.chunk_at(idx);
+        chunk.value(idx)
+    }
+
+    /// Values of every row in order, ignoring nulls.
+    pub fn values(&self) -> impl Iterator<Item = N::Native> + Clone + 'a {
+        self.chunks.clone().into_iter().flat_map(|chunk| chunk.values().iter().copied())
+    }
+
+    /// Values of every row as one slice of the Arrow buffer, if the column
+    /// has at most one chunk.
+    pub fn as_slice(&self) -> Option<&'a [N::Native]> {
+        match self.chunks.as_slice() {
+            [] => Some(&[]),
+            [chunk] => Some(chunk.values()),
+            _ => None,
+        }
+    }
+
+    /// Values of every row in order, with `None` for nulls.
+    pub fn iter(&self) -> impl Iterator<Item = Option<N::Native>> + 'a {
+        self.chunks.clone().into_iter().flat_map(|chunk| chunk.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::{
+        array::UInt64Array,
+        datatypes::{DataType, Field},
+    };
+
+    fn chunk(schema: &SchemaRef, values: Vec<u64>) -> RecordBatch {
+        RecordBatch::try_new(schema.clone(), vec![Arc::new(UInt64Array::from(values))]).unwrap()
+    }
+
+    #[test]
+    fn test_chunks() {
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::UInt64, false)]));
+        let mut record_batch = ChunkedRecordBatch::from(chunk(&schema, vec![1, 2]));
+        record_batch.push(chunk(&schema, vec![])).unwrap();
+        record_batch.push(chunk(&schema, vec![3, 4, 5])).unwrap();
+        assert_eq!(record_batch.chunks().len(), 2);
+        assert_eq!(record_batch.num_rows(), 5);
+
+        let ids = record_batch.column::<UInt64Array>(0).unwrap();
+        assert_eq!(ids.values().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+        assert_eq!(ids.value(1), 2);
+        assert_eq!(ids.value(2), 3);
+
+        let keep = BooleanArray::from(vec![false, true, true, false, false]);
+        let filtered = record_batch.filter(&keep).unwrap();
+        assert_eq!(filtered.chunks().len(), 2);
+        assert_eq!(filtered.to_record_batch(), chunk(&schema, vec![2, 3]));
+
+        let uncompacted = record_batch.clone();
+        record_batch.compact();
+        assert_eq!(record_batch.chunks().len(), 1);
+        assert_eq!(record_batch, uncompacted);
+    }
+
+    #[test]
+    fn test_push_new_column() {
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::UInt64, false)]));
+        let mut record_batch = ChunkedRecordBatch::from(chunk(&schema, vec![1]));
+        let labelled = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::UInt64, false),
+            Field::new("label", DataType::UInt64, false),
+        ]));
+        record_batch
+            .push(
+                RecordBatch::try_new(
+                    labelled,
+                    vec![
+                        Arc::new(UInt64Array::from(vec![2])),
+                        Arc::new(UInt64Array::from(vec![7])),
+                    ],
+                )
+                .unwrap(),
+            )
+            .unwrap();
+
+        assert!(record_batch.schema_ref().field_with_name("label").unwrap().is_nullable());
+        let labels = record_batch.column::<UInt64Array>(1).unwrap();
+        assert_eq!(labels.iter().collect::<Vec<_>>(), vec![None, Some(7)]);
+    }
+}
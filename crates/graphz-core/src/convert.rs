@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use snafu::prelude::*;
+
+use crate::{
+    edge::EdgeRecordBatch,
+    graph::{EdgeDataSnafu, NodeDataSnafu},
+    node::NodeRecordBatch,
+    types::{NodeId, Weight},
+    Graph, GraphError, GraphKind,
+};
+
+impl Graph {
+    /// Builds a graph from the weighted targets of each node.
+    ///
+    /// Every key is a node, even without targets. Undirected graphs expect
+    /// each edge to be listed once.
+    pub fn from_adjacency_list(
+        adjacency_list: HashMap<NodeId, Vec<(NodeId, Weight)>>,
+        kind: GraphKind,
+    ) -> Result<Graph, GraphError> {
+        let mut node_ids = adjacency_list
+            .iter()
+            .flat_map(|(&source_id, targets)| {
+                std::iter::once(source_id).chain(targets.iter().map(|&(target_id, _)| target_id))
+            })
+            .collect::<Vec<_>>();
+        node_ids.sort_unstable();
+        node_ids.dedup();
+
+        // Sorting by source keeps the edge order independent of the hash map.
+        let mut adjacency_list = adjacency_list.into_iter().collect::<Vec<_>>();
+        adjacency_list.sort_unstable_by_key(|&(source_id, _)| source_id);
+        let num_edges = adjacency_list.iter().map(|(_, targets)| targets.len()).sum();
+        let mut sources = Vec::with_capacity(num_edges);
+        let mut targets = Vec::with_capacity(num_edges);
+        let mut weights = Vec::with_capacity(num_edges);
+        for (source_id, edges) in adjacency_list {
+            for (target_id, weight) in edges {
+                sources.push(source_id);
+                targets.push(target_id);
+                weights.push(weight);
+            }
+        }
+        Self::from_node_ids_and_edge_columns(node_ids, sources, targets, weights, kind)
+    }
+
+    /// Builds a graph of `num_nodes` nodes, with ids `0..num_nodes`, from a
+    /// dense row-major adjacency matrix. Zero and NaN entries are no edge.
+    ///
+    /// Undirected graphs only read the upper triangle of the matrix.
+    pub fn from_adjacency_matrix(
+        matrix: &[f64],
+        num_nodes: usize,
+        kind: GraphKind,
+    ) -> Result<Graph, GraphError> {
+        let invalid = || GraphError::InvalidAdjacencyMatrix { len: matrix.len(), num_nodes };
+        let num_node_ids = NodeId::try_from(num_nodes).map_err(|_| invalid())?;
+        if num_nodes.checked_mul(num_nodes) != Some(matrix.len()) {
+            return Err(invalid());
+        }
+        let mut sources = Vec::new();
+        let mut targets = Vec::new();
+        let mut weights = Vec::new();
+        for (source_id, row) in matrix.chunks_exact(num_nodes.max(1)).enumerate() {
+            let first_target = match kind {
+                GraphKind::Directed => 0,
+                GraphKind::Undirected => source_id,
+            };
+            for (target_id, &weight) in row.iter().enumerate().skip(first_target) {
+                if weight != 0.0 && !weight.is_nan() {
+                    sources.push(source_id as NodeId);
+                    targets.push(target_id as NodeId);
+                    weights.push(weight);
+                }
+            }
+        }
+        let node_ids = (0..num_node_ids).collect();
+        Self::from_node_ids_and_edge_columns(node_ids, sources, targets, weights, kind)
+    }
+
+    /// Builds a graph of the given nodes and edge columns.
+    pub(crate) fn from_node_ids_and_edge_columns(
+        node_ids: Vec<NodeId>,
+        sources: Vec<NodeId>,
+        targets: Vec<NodeId>,
+        weights: Vec<Weight>,
+        kind: GraphKind,
+    ) -> Result<Graph, GraphError> {
+        let edge_record_batch = EdgeRecordBatch::try_from_columns(sources, targets, Some(weights))
+            .context(EdgeDataSnafu {})?;
+        Graph::from_arrow_record_batches(
+            NodeRecordBatch::from_node_ids(node_ids),
+            edge_record_batch.with_kind(kind),
+        )
+    }
+
+    /// Returns the dense row-major adjacency matrix of the graph, with rows
+    /// and columns in node row order. Parallel edges add up, and absent
+    /// edges are zero. Missing weights follow the [`crate::MissingWeight`] policy.
+    ///
+    /// Undirected graphs give a symmetric matrix.
+    pub fn to_adjacency_matrix(&self) -> Result<Vec<f64>, GraphError> {
+        let num_nodes = self.num_nodes();
+        let mut matrix = vec![0.0; num_nodes * num_nodes];
+        let row = |node_id| match self.node_row(node_id).context(NodeDataSnafu {})? {
+            Some(row) => Ok(row),
+            None => Err(GraphError::NodeNotFound { node_id }),
+        };
+        let sources = self.edge_data().source_ids().context(EdgeDataSnafu {})?;
+        let targets = self.edge_data().target_ids().context(EdgeDataSnafu {})?;
+        let weights = self.edge_data().resolved_weights().context(EdgeDataSnafu {})?;
+        for ((source_id, target_id), weight) in sources.values().zip(targets.values()).zip(weights)
+        {
+            let (source, target) = (row(source_id)?, row(target_id)?);
+            // Edges skipped by the missing weight policy count as absent
+            let Some(weight) = weight else {
+                continue;
+            };
+            matrix[source * num_nodes + target] += weight;
+            if self.kind() == GraphKind::Undirected && source != target {
+                matrix[target * num_nodes + source] += weight;
+            }
+        }
+        Ok(matrix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjacency_list() {
+        let adjacency_list =
+            HashMap::from([(1, vec![(2, 1.0), (3, 4.0)]), (2, vec![(3, 2.0)]), (5, vec![])]);
+        let graph = Graph::from_adjacency_list(adjacency_list, GraphKind::Directed).unwrap();
+        assert_eq!(graph.num_nodes(), 4);
+        assert_eq!(graph.num_edges(), 3);
+        assert_eq!(graph.neighbors_with_weights(1).unwrap(), vec![(2, 1.0), (3, 4.0)]);
+        assert!(graph.contains_node(5).unwrap());
+        assert_eq!(
+            graph.to_adjacency_matrix().unwrap(),
+            vec![
+                0.0, 1.0, 4.0, 0.0, //
+                0.0, 0.0, 2.0, 0.0, //
+                0.0, 0.0, 0.0, 0.0, //
+                0.0, 0.0, 0.0, 0.0,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_adjacency_matrix() {
+        #[rustfmt::skip]
+        let matrix = vec![
+            0.0, 2.0, f64::NAN,
+            2.0, 0.0, 3.0,
+            0.0, 3.0, 1.0,
+        ];
+        let graph = Graph::from_adjacency_matrix(&matrix, 3, GraphKind::Directed).unwrap();
+        assert_eq!(graph.num_edges(), 5);
+        assert_eq!(graph.neighbors(2).unwrap(), vec![1, 2]);
+
+        let graph = Graph::from_adjacency_matrix(&matrix, 3, GraphKind::Undirected).unwrap();
+        assert_eq!(graph.num_edges(), 3);
+        assert_eq!(graph.neighbors(1).unwrap(), vec![0, 2]);
+        assert_eq!(
+            graph.to_adjacency_matrix().unwrap(),
+            vec![
+                0.0, 2.0, 0.0, //
+                2.0, 0.0, 3.0, //
+                0.0, 3.0, 1.0,
+            ]
+        );
+
+        assert!(matches!(
+            Graph::from_adjacency_matrix(&matrix, 2, GraphKind::Directed),
+            Err(GraphError::InvalidAdjacencyMatrix { len: 9, num_nodes: 2 })
+        ));
+        assert!(matches!(
+            Graph::from_adjacency_matrix(&matrix, usize::MAX, GraphKind::Directed),
+            Err(GraphError::InvalidAdjacencyMatrix { .. })
+        ));
+    }
+}
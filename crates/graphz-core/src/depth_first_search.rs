@@ -1,8 +1,12 @@
-use crate::{types::NodeId, Graph};
+use crate::{graph_ref::GraphRef, types::NodeId};
 use std::collections::HashSet;
 use tracing::{debug, info};
 
-pub fn depth_first_search(graph: &Graph, start: NodeId, end: NodeId) -> Option<Vec<NodeId>> {
+pub fn depth_first_search<G: GraphRef>(
+    graph: &G,
+    start: NodeId,
+    end: NodeId,
+) -> Option<Vec<NodeId>> {
     info!("Starting DFS from node {} to node {}", start, end);
     let mut visited = HashSet::new();
     let mut stack = Vec::new();
@@ -21,16 +25,12 @@ pub fn depth_first_search(graph: &Graph, start: NodeId, end: NodeId) -> Option<V
             debug!("Visiting node {}", current);
             visited.insert(current);
 
-            for idx in 0..graph.num_edges() {
-                let source_id = graph.source_id(idx).unwrap();
-                let target_id = graph.target_id(idx).unwrap();
-                if source_id == current {
-                    if !visited.contains(&target_id) {
-                        let mut new_path = current_path.clone();
-                        new_path.push(target_id);
-                        debug!("Adding node {} to stack with path: {:?}", target_id, new_path);
-                        stack.push((target_id, new_path));
-                    }
+            for neighbor in graph.neighbors(current) {
+                if !visited.contains(&neighbor) {
+                    let mut new_path = current_path.clone();
+                    new_path.push(neighbor);
+                    debug!("Adding node {} to stack with path: {:?}", neighbor, new_path);
+                    stack.push((neighbor, new_path));
                 }
             }
         } else {
@@ -1,22 +1,24 @@
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap};
 
-use crate::types::NodeId;
-use crate::Graph;
+use crate::graph_ref::GraphRef;
+use crate::types::{EdgeId, NodeId, Weight};
 
 /// State represents a node in the path-finding process
 /// cost: the total cost to reach this node
 /// position: the current node's ID
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, PartialEq)]
 struct State {
-    cost: u32,
+    cost: Weight,
     position: NodeId,
 }
 
+impl Eq for State {}
+
 // Custom ordering for State to create a min-heap based on cost
 impl Ord for State {
     fn cmp(&self, other: &Self) -> Ordering {
-        other.cost.cmp(&self.cost)
+        other.cost.total_cmp(&self.cost)
     }
 }
 
@@ -29,29 +31,51 @@ impl PartialOrd for State {
 /// Implements Dijkstra's shortest path algorithm
 /// Returns the shortest path from start to end as a vector of node IDs
 /// Returns None if no path exists
-pub fn dijkstra_search(graph: &Graph, start: NodeId, end: NodeId) -> Option<Vec<NodeId>> {
+pub fn dijkstra_search<G: GraphRef>(graph: &G, start: NodeId, end: NodeId) -> Option<Vec<NodeId>> {
+    dijkstra(graph, start, end).map(|(path, _)| path)
+}
+
+/// Like [`dijkstra_search`], but returns the IDs of the edges along the
+/// shortest path, telling apart parallel edges between the same nodes
+pub fn dijkstra_search_edges<G: GraphRef>(
+    graph: &G,
+    start: NodeId,
+    end: NodeId,
+) -> Option<Vec<EdgeId>> {
+    dijkstra(graph, start, end).map(|(_, edges)| edges)
+}
+
+/// Returns the nodes and edges of the shortest path
+fn dijkstra<G: GraphRef>(
+    graph: &G,
+    start: NodeId,
+    end: NodeId,
+) -> Option<(Vec<NodeId>, Vec<EdgeId>)> {
     // Track shortest distance to each node
-    let mut dist: HashMap<NodeId, u32> = HashMap::new();
-    // Track previous node in optimal path
-    let mut prev: HashMap<NodeId, NodeId> = HashMap::new();
+    let mut dist: HashMap<NodeId, Weight> = HashMap::new();
+    // Track previous node and edge in optimal path
+    let mut prev: HashMap<NodeId, (NodeId, EdgeId)> = HashMap::new();
     // Priority queue for nodes to visit
     let mut heap = BinaryHeap::new();
 
     // Initialize start node
-    dist.insert(start, 0);
-    heap.push(State { cost: 0, position: start });
+    dist.insert(start, 0.0);
+    heap.push(State { cost: 0.0, position: start });
 
     while let Some(State { cost, position }) = heap.pop() {
         // If we reached the end, reconstruct and return the path
         if position == end {
             let mut path = vec![end];
+            let mut edges = Vec::new();
             let mut current = end;
-            while let Some(&previous) = prev.get(&current) {
+            while let Some(&(previous, edge)) = prev.get(&current) {
                 path.push(previous);
+                edges.push(edge);
                 current = previous;
             }
             path.reverse();
-            return Some(path);
+            edges.reverse();
+            return Some((path, edges));
         }
 
         // Skip if we've found a better path
@@ -60,14 +84,18 @@ pub fn dijkstra_search(graph: &Graph, start: NodeId, end: NodeId) -> Option<Vec<
         }
 
         // Explore neighbors
-        for (neighbor, weight) in graph.neighbors_with_weights(position).unwrap() {
-            let next = State { cost: cost + weight as u32, position: neighbor };
+        for edge in graph.outgoing_edges(position) {
+            // Edges skipped by the missing weight policy are not followed
+            let Some(weight) = edge.weight else {
+                continue;
+            };
+            let next = State { cost: cost + weight, position: edge.target };
 
             // Update if we found a shorter path
             if !dist.contains_key(&next.position) || next.cost < dist[&next.position] {
                 heap.push(next);
                 dist.insert(next.position, next.cost);
-                prev.insert(next.position, position);
+                prev.insert(next.position, (position, edge.id));
             }
         }
     }
@@ -107,4 +135,30 @@ mod tests {
         let path = dijkstra_search(&graph, 1, 5);
         assert_eq!(path, None);
     }
+
+    #[test]
+    fn test_dijkstra_search_edges() {
+        let edges = vec![
+            Edge::builder().source_id(1).target_id(2).weight(5.0).build().unwrap(),
+            Edge::builder().source_id(1).target_id(2).weight(1.0).build().unwrap(),
+            Edge::builder().source_id(2).target_id(3).weight(2.0).build().unwrap(),
+        ];
+        let graph = Graph::builder().edges(edges).build().unwrap();
+        assert_eq!(dijkstra_search_edges(&graph, 1, 3), Some(vec![1, 2]));
+        assert_eq!(dijkstra_search_edges(&graph, 1, 1), Some(vec![]));
+        assert_eq!(dijkstra_search_edges(&graph, 3, 1), None);
+    }
+
+    #[test]
+    fn test_fractional_weights() {
+        let edges = vec![
+            Edge::builder().source_id(1).target_id(2).weight(0.6).build().unwrap(),
+            Edge::builder().source_id(2).target_id(3).weight(0.6).build().unwrap(),
+            Edge::builder().source_id(1).target_id(3).weight(1.0).build().unwrap(),
+        ];
+        let graph = Graph::builder().edges(edges).build().unwrap();
+        // Truncated to integers, the detour would cost 0 and win.
+        assert_eq!(dijkstra_search(&graph, 1, 3), Some(vec![1, 3]));
+        assert_eq!(dijkstra_search(&graph, 1, 3), crate::a_search(&graph, 1, 3));
+    }
 }
@@ -1,12 +1,35 @@
-use crate::types::{NodeId, Weight};
+use crate::{
+    adjacency::AdjacencyIndex,
+    chunked::{ChunkedColumn, ChunkedRecordBatch},
+    graph::GraphKind,
+    property::{self, DictionaryStringArray, PropertyValue},
+    temporal::EdgeTime,
+    types::{EdgeId, NodeId, NodeIdArray, NodeIdType, Timestamp, Weight},
+};
 use arrow::{
-    array::{PrimitiveArray, RecordBatch},
-    datatypes::{DataType, Field, Float64Type, Schema, UInt32Type},
+    array::{new_null_array, Array, ArrayRef, BooleanArray, PrimitiveArray, RecordBatch},
+    datatypes::{
+        ArrowPrimitiveType, DataType, Field, Float64Type, Int64Type, Schema, SchemaRef, UInt64Type,
+    },
     error::ArrowError,
 };
 use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
 use snafu::prelude::*;
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, iter,
+    str::FromStr,
+    sync::Arc,
+};
+
+/// Schema metadata key holding the [`GraphKind`] of the edges.
+pub const GRAPH_KIND_METADATA_KEY: &str = "graphz.kind";
+/// Schema metadata key holding the lowest edge id never handed out, which
+/// keeps the ids of removed edges from being reused.
+pub const NEXT_EDGE_ID_METADATA_KEY: &str = "graphz.next_edge_id";
+/// Schema metadata key holding the [`MissingWeight`] policy of the edges.
+pub const MISSING_WEIGHT_METADATA_KEY: &str = "graphz.missing_weight";
 
 #[derive(Debug, Snafu)]
 pub enum EdgeDataError {
@@ -14,12 +37,67 @@ pub enum EdgeDataError {
     ColumnNotFound,
     ColumnTypeMismatch { data_type: String },
     FailedToAddEdges { source: ArrowError },
+    InvalidGraphKind { kind: String },
+    InvalidRecordBatches { source: ArrowError },
+    MultipleChunks { num_chunks: usize },
+    InvalidMissingWeight { policy: String },
+    MissingWeight { edge_id: EdgeId },
+    DuplicateEdgeId { edge_id: EdgeId },
+    EdgeIdOverflow,
 }
 
 type Result<T, E = EdgeDataError> = std::result::Result<T, E>;
 
-#[derive(Debug, strum::EnumString, strum::AsRefStr)]
+/// How weighted algorithms treat edges without a weight. The weight column
+/// itself keeps the nulls.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MissingWeight {
+    /// Follows the edge with the given weight.
+    Default(Weight),
+    /// Leaves the edge out of weighted algorithms, such as shortest paths.
+    /// Neighbor lookups and traversals still follow it.
+    Skip,
+    /// Fails with [`EdgeDataError::MissingWeight`].
+    Error,
+}
+
+impl Default for MissingWeight {
+    fn default() -> Self {
+        Self::Default(0.0)
+    }
+}
+
+impl fmt::Display for MissingWeight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default(weight) => write!(f, "default:{weight}"),
+            Self::Skip => write!(f, "skip"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl FromStr for MissingWeight {
+    type Err = EdgeDataError;
+
+    fn from_str(policy: &str) -> Result<Self> {
+        match policy {
+            "skip" => Ok(Self::Skip),
+            "error" => Ok(Self::Error),
+            _ => policy
+                .strip_prefix("default:")
+                .and_then(|weight| weight.parse().ok())
+                .map(Self::Default)
+                .ok_or_else(|| EdgeDataError::InvalidMissingWeight { policy: policy.to_string() }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, strum::EnumString, strum::AsRefStr)]
 pub enum Attribute {
+    // Stable identifier of the edge, distinguishing parallel edges
+    #[strum(serialize = "id")]
+    Id,
     // Source
     #[strum(serialize = "source")]
     Source,
@@ -30,213 +108,874 @@ pub enum Attribute {
     // Weight of the edge: positive flows source->target, negative flows target->source
     #[strum(serialize = "weight")]
     Weight,
+
+    // Optional columns, only present if some edge has a value
+    // Time of the event recorded by the edge
+    #[strum(serialize = "timestamp")]
+    Timestamp,
+    // Start of the interval the edge is valid in, inclusive
+    #[strum(serialize = "valid_from")]
+    ValidFrom,
+    // End of the interval the edge is valid in, exclusive
+    #[strum(serialize = "valid_to")]
+    ValidTo,
+    // Dictionary-encoded type of the relation, such as OWNS or STARS
+    #[strum(serialize = "edge_type")]
+    EdgeType,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Builder)]
+#[derive(Debug, Clone, PartialEq, Builder, Serialize, Deserialize)]
 pub struct Edge {
+    // Assigned when the edge is stored if left empty
+    #[builder(setter(into, strip_option), default)]
+    #[serde(default)]
+    pub id: Option<EdgeId>,
     pub source_id: NodeId,
     pub target_id: NodeId,
     #[builder(setter(into, strip_option), default)]
+    #[serde(default)]
     pub weight: Option<Weight>,
+    #[builder(setter(into, strip_option), default)]
+    #[serde(default)]
+    pub timestamp: Option<Timestamp>,
+    #[builder(setter(into, strip_option), default)]
+    #[serde(default)]
+    pub valid_from: Option<Timestamp>,
+    #[builder(setter(into, strip_option), default)]
+    #[serde(default)]
+    pub valid_to: Option<Timestamp>,
+    // Shared by the edges of a type, so copying edges does not copy it
+    #[builder(setter(into, strip_option), default)]
+    #[serde(default)]
+    pub edge_type: Option<Arc<str>>,
 }
 
 impl Edge {
     pub fn builder() -> EdgeBuilder {
         EdgeBuilder::default()
     }
+
+    /// When the edge can be followed.
+    pub fn time(&self) -> EdgeTime {
+        EdgeTime { timestamp: self.timestamp, valid_from: self.valid_from, valid_to: self.valid_to }
+    }
 }
 
+/// An edge between nodes identified by their external keys.
+#[derive(Debug, Clone, PartialEq, Builder, Serialize, Deserialize)]
+pub struct KeyedEdge {
+    #[builder(setter(into))]
+    pub source: String,
+    #[builder(setter(into))]
+    pub target: String,
+    #[builder(setter(into, strip_option), default)]
+    #[serde(default)]
+    pub weight: Option<Weight>,
+    #[builder(setter(into, strip_option), default)]
+    #[serde(default)]
+    pub edge_type: Option<Arc<str>>,
+}
+
+impl KeyedEdge {
+    pub fn builder() -> KeyedEdgeBuilder {
+        KeyedEdgeBuilder::default()
+    }
+}
+
+/// Edges stored as one or more record batches sharing a schema.
+///
+/// Appending edges adds a chunk, so the existing rows are not copied.
 #[derive(Debug, Clone, PartialEq)]
-pub struct EdgeRecordBatch(RecordBatch);
+pub struct EdgeRecordBatch(ChunkedRecordBatch);
 
 impl EdgeRecordBatch {
     pub fn schema() -> Schema {
         Schema::new(vec![
-            Field::new(Attribute::Source.as_ref(), DataType::UInt32, false),
-            Field::new(Attribute::Target.as_ref(), DataType::UInt32, false),
+            Field::new(Attribute::Id.as_ref(), DataType::UInt64, false),
+            Field::new(Attribute::Source.as_ref(), NodeIdType::DATA_TYPE, false),
+            Field::new(Attribute::Target.as_ref(), NodeIdType::DATA_TYPE, false),
             Field::new(Attribute::Weight.as_ref(), DataType::Float64, true),
         ])
     }
 
-    pub fn record_batch(&self) -> &RecordBatch {
-        &self.0
+    /// Builds edges from columns of sources, targets and optional weights,
+    /// moving the vectors into the Arrow buffers. Edges are numbered in order.
+    ///
+    /// Without `weights`, every weight is null and follows the
+    /// [`MissingWeight`] policy.
+    pub fn try_from_columns(
+        sources: Vec<NodeId>,
+        targets: Vec<NodeId>,
+        weights: Option<Vec<Weight>>,
+    ) -> Result<Self> {
+        let weights: ArrayRef = match weights {
+            Some(weights) => Arc::new(PrimitiveArray::<Float64Type>::from(weights)),
+            None => new_null_array(&DataType::Float64, sources.len()),
+        };
+        let record_batch = RecordBatch::try_new(
+            Self::schema().into(),
+            vec![
+                Arc::new(PrimitiveArray::<UInt64Type>::from_iter_values(0..sources.len() as u64)),
+                Arc::new(NodeIdArray::from(sources)),
+                Arc::new(NodeIdArray::from(targets)),
+                weights,
+            ],
+        )
+        .context(InvalidRecordBatchesSnafu {})?;
+        Ok(Self(record_batch.into()))
+    }
+
+    /// Builds edges from record batches with the fields of `schema`.
+    pub fn try_from_record_batches(
+        schema: SchemaRef,
+        record_batches: Vec<RecordBatch>,
+    ) -> Result<Self> {
+        let record_batch = ChunkedRecordBatch::try_new(schema, record_batches)
+            .context(InvalidRecordBatchesSnafu {})?;
+        Ok(Self(record_batch))
+    }
+
+    pub fn schema_ref(&self) -> &SchemaRef {
+        self.0.schema_ref()
+    }
+
+    /// The chunks holding the edges, in row order.
+    pub fn record_batches(&self) -> &[RecordBatch] {
+        self.0.chunks()
+    }
+
+    /// Returns the edges as one record batch, concatenating the chunks if
+    /// there is more than one.
+    pub fn to_record_batch(&self) -> RecordBatch {
+        self.0.to_record_batch()
+    }
+
+    /// Merges the chunks into one.
+    pub fn compact(&mut self) {
+        self.0.compact()
     }
 
     pub fn num_edges(&self) -> usize {
-        self.record_batch().num_rows()
+        self.0.num_rows()
+    }
+
+    /// Reads the graph kind from the schema metadata, defaulting to directed.
+    pub fn kind(&self) -> Result<GraphKind> {
+        match self.schema_ref().metadata().get(GRAPH_KIND_METADATA_KEY) {
+            Some(kind) => GraphKind::from_str(kind)
+                .map_err(|_| EdgeDataError::InvalidGraphKind { kind: kind.clone() }),
+            None => Ok(GraphKind::default()),
+        }
+    }
+
+    /// Records the graph kind in the schema metadata.
+    pub fn with_kind(self, kind: GraphKind) -> Self {
+        self.with_metadata(GRAPH_KIND_METADATA_KEY, kind.as_ref().to_string())
+    }
+
+    /// Reads the missing weight policy from the schema metadata.
+    pub fn missing_weight(&self) -> Result<MissingWeight> {
+        match self.schema_ref().metadata().get(MISSING_WEIGHT_METADATA_KEY) {
+            Some(policy) => MissingWeight::from_str(policy),
+            None => Ok(MissingWeight::default()),
+        }
+    }
+
+    /// Records the missing weight policy in the schema metadata.
+    pub fn with_missing_weight(self, policy: MissingWeight) -> Self {
+        self.with_metadata(MISSING_WEIGHT_METADATA_KEY, policy.to_string())
+    }
+
+    pub(crate) fn with_next_edge_id(self, next_id: EdgeId) -> Self {
+        self.with_metadata(NEXT_EDGE_ID_METADATA_KEY, next_id.to_string())
+    }
+
+    fn with_metadata(self, key: &str, value: String) -> Self {
+        Self(self.0.with_metadata(key, value))
+    }
+
+    fn column<T: Array + 'static>(&self, name: &str) -> Result<ChunkedColumn<'_, T>> {
+        let (idx, field) =
+            self.schema_ref().column_with_name(name).ok_or(EdgeDataError::ColumnNotFound)?;
+        self.0.column(idx).ok_or_else(|| EdgeDataError::ColumnTypeMismatch {
+            data_type: field.data_type().to_string(),
+        })
+    }
+
+    pub fn edge_ids(&self) -> Result<ChunkedColumn<'_, PrimitiveArray<UInt64Type>>> {
+        self.column(Attribute::Id.as_ref())
+    }
+
+    pub fn source_ids(&self) -> Result<ChunkedColumn<'_, NodeIdArray>> {
+        self.column(Attribute::Source.as_ref())
+    }
+
+    pub fn target_ids(&self) -> Result<ChunkedColumn<'_, NodeIdArray>> {
+        self.column(Attribute::Target.as_ref())
+    }
+
+    pub fn weights(&self) -> Result<ChunkedColumn<'_, PrimitiveArray<Float64Type>>> {
+        self.column(Attribute::Weight.as_ref())
+    }
+
+    /// Returns true if the edges have the optional column `attribute`.
+    pub fn has_column(&self, attribute: Attribute) -> bool {
+        self.schema_ref().column_with_name(attribute.as_ref()).is_some()
+    }
+
+    /// Values of the optional time column `attribute`, `None` for every edge
+    /// if the column is missing.
+    fn optional_times(
+        &self,
+        attribute: Attribute,
+    ) -> Result<impl Iterator<Item = Option<Timestamp>> + '_> {
+        let column = match self.has_column(attribute) {
+            true => Some(self.column::<PrimitiveArray<Int64Type>>(attribute.as_ref())?),
+            false => None,
+        };
+        let values = column.into_iter().flat_map(|column| column.iter());
+        Ok(values.chain(iter::repeat(None)).take(self.num_edges()))
+    }
+
+    /// Iterates over the timestamps and validity intervals of the edges in
+    /// row order.
+    pub fn times_iter(&self) -> Result<impl Iterator<Item = EdgeTime> + '_> {
+        let timestamps = self.optional_times(Attribute::Timestamp)?;
+        let valid_from = self.optional_times(Attribute::ValidFrom)?;
+        let valid_to = self.optional_times(Attribute::ValidTo)?;
+        Ok(timestamps.zip(valid_from).zip(valid_to).map(|((timestamp, valid_from), valid_to)| {
+            EdgeTime { timestamp, valid_from, valid_to }
+        }))
+    }
+
+    /// Reads the timestamp and validity interval of the edge at `idx`.
+    pub fn time(&self, idx: usize) -> Result<EdgeTime> {
+        if idx >= self.num_edges() {
+            return Err(EdgeDataError::IndexOutOfBounds);
+        }
+        let value = |attribute: Attribute| -> Result<Option<Timestamp>> {
+            if !self.has_column(attribute) {
+                return Ok(None);
+            }
+            let (data, idx) =
+                self.column::<PrimitiveArray<Int64Type>>(attribute.as_ref())?.chunk_at(idx);
+            Ok(data.is_valid(idx).then(|| data.value(idx)))
+        };
+        Ok(EdgeTime {
+            timestamp: value(Attribute::Timestamp)?,
+            valid_from: value(Attribute::ValidFrom)?,
+            valid_to: value(Attribute::ValidTo)?,
+        })
+    }
+
+    /// Returns true if the edges carry the optional edge type column.
+    pub fn has_edge_types(&self) -> bool {
+        property::has_dictionary_column(self.schema_ref(), Attribute::EdgeType.as_ref())
+    }
+
+    pub fn edge_types(&self) -> Result<ChunkedColumn<'_, DictionaryStringArray>> {
+        self.column(Attribute::EdgeType.as_ref())
+    }
+
+    /// Reads the type of the edge at `idx`, `None` if the edges have no types.
+    pub fn edge_type(&self, idx: usize) -> Result<Option<&str>> {
+        if idx >= self.num_edges() {
+            return Err(EdgeDataError::IndexOutOfBounds);
+        }
+        if !self.has_edge_types() {
+            return Ok(None);
+        }
+        let (edge_types, idx) = self.edge_types()?.chunk_at(idx);
+        Ok(property::dictionary_value(edge_types, idx))
+    }
+
+    /// Types of the edges in row order, with `None` for every edge if the
+    /// column is missing.
+    fn edge_types_iter(&self) -> Result<impl Iterator<Item = Option<Arc<str>>> + '_> {
+        let edge_types = match self.has_edge_types() {
+            true => Some(self.edge_types()?),
+            false => None,
+        };
+        let chunks = edge_types.map_or_else(Vec::new, |edge_types| edge_types.chunks().to_vec());
+        let values = chunks.into_iter().flat_map(property::dictionary_values);
+        Ok(values.chain(iter::repeat(None)).take(self.num_edges()))
+    }
+
+    /// Names of the user-defined property columns.
+    pub fn property_names(&self) -> Vec<&str> {
+        property::property_names(self.schema_ref(), &Self::schema())
+            .into_iter()
+            .filter(|name| Attribute::from_str(name).is_err())
+            .collect()
+    }
+
+    /// Reads the value of the property column `name` for the edge at `idx`.
+    pub fn property<T: PropertyValue>(&self, idx: usize, name: &str) -> Result<Option<T>> {
+        if idx >= self.num_edges() {
+            return Err(EdgeDataError::IndexOutOfBounds);
+        }
+        let (data, idx) = self.column::<T::Array>(name)?.chunk_at(idx);
+        Ok(data.is_valid(idx).then(|| T::value(data, idx)))
+    }
+
+    pub fn edge_id(&self, idx: usize) -> Result<EdgeId> {
+        if idx >= self.num_edges() {
+            return Err(EdgeDataError::IndexOutOfBounds);
+        }
+        Ok(self.edge_ids()?.value(idx))
     }
 
     pub fn source_id(&self, idx: usize) -> Result<NodeId> {
         if idx >= self.num_edges() {
             return Err(EdgeDataError::IndexOutOfBounds);
         }
-        let column = self
-            .record_batch()
-            .column_by_name(Attribute::Source.as_ref())
-            .ok_or(EdgeDataError::ColumnNotFound)?;
-        let data = column.as_any().downcast_ref::<PrimitiveArray<UInt32Type>>().ok_or(
-            EdgeDataError::ColumnTypeMismatch { data_type: column.data_type().to_string() },
-        )?;
-        Ok(data.value(idx))
+        Ok(self.source_ids()?.value(idx))
     }
 
     pub fn target_id(&self, idx: usize) -> Result<NodeId> {
         if idx >= self.num_edges() {
             return Err(EdgeDataError::IndexOutOfBounds);
         }
-        let column = self
-            .record_batch()
-            .column_by_name(Attribute::Target.as_ref())
-            .ok_or(EdgeDataError::ColumnNotFound)?;
-        let data = column.as_any().downcast_ref::<PrimitiveArray<UInt32Type>>().ok_or(
-            EdgeDataError::ColumnTypeMismatch { data_type: column.data_type().to_string() },
-        )?;
-        Ok(data.value(idx))
+        Ok(self.target_ids()?.value(idx))
     }
+
+    /// Scans every edge for the targets of `node`.
+    ///
+    /// Prefer [`crate::Graph::neighbors`], which answers from an adjacency index.
     pub fn neighbors(&self, node: NodeId) -> Result<Vec<NodeId>> {
-        let mut neighbors = Vec::new();
-        for i in 0..self.num_edges() {
-            let edge = self.edge(i)?;
-            if edge.source_id == node {
-                neighbors.push(edge.target_id);
-            }
-        }
-        Ok(neighbors)
+        let sources = self.source_ids()?;
+        let targets = self.target_ids()?;
+        Ok(sources
+            .values()
+            .zip(targets.values())
+            .filter(|(source_id, _)| *source_id == node)
+            .map(|(_, target_id)| target_id)
+            .collect())
     }
 
     pub fn weight(&self, idx: usize) -> Result<Option<Weight>> {
         if idx >= self.num_edges() {
             return Err(EdgeDataError::IndexOutOfBounds);
         }
-        let column = self
-            .record_batch()
-            .column_by_name(Attribute::Weight.as_ref())
-            .ok_or(EdgeDataError::ColumnNotFound)?;
-        let data = column.as_any().downcast_ref::<PrimitiveArray<Float64Type>>().ok_or(
-            EdgeDataError::ColumnTypeMismatch { data_type: column.data_type().to_string() },
-        )?;
-        Ok(data.value(idx).into())
+        let (weights, idx) = self.weights()?.chunk_at(idx);
+        Ok(weights.is_valid(idx).then(|| weights.value(idx)))
     }
 
-    pub fn neighbors_with_weights(&self, node: NodeId) -> Result<Vec<(NodeId, Weight)>> {
-        let mut neighbors = Vec::new();
-        for i in 0..self.num_edges() {
-            let edge = self.edge(i)?;
-            if edge.source_id == node {
-                neighbors.push((edge.target_id, edge.weight.unwrap_or_default()));
-            }
+    /// Weights of the edges in row order, with missing weights resolved by
+    /// the [`MissingWeight`] policy. Skipped edges are `None`.
+    pub fn resolved_weights(&self) -> Result<Vec<Option<Weight>>> {
+        let policy = self.missing_weight()?;
+        let edge_ids = self.edge_ids()?;
+        let weights = self.weights()?;
+        weights
+            .iter()
+            .zip(edge_ids.values())
+            .map(|(weight, edge_id)| match (weight, policy) {
+                (Some(weight), _) | (None, MissingWeight::Default(weight)) => Ok(Some(weight)),
+                (None, MissingWeight::Skip) => Ok(None),
+                (None, MissingWeight::Error) => Err(EdgeDataError::MissingWeight { edge_id }),
+            })
+            .collect()
+    }
+
+    /// Fails if the policy is [`MissingWeight::Error`] and an edge has no weight.
+    pub(crate) fn check_missing_weights(&self) -> Result<()> {
+        if self.missing_weight()? != MissingWeight::Error {
+            return Ok(());
+        }
+        let edge_ids = self.edge_ids()?;
+        match self.weights()?.iter().zip(edge_ids.values()).find(|(weight, _)| weight.is_none()) {
+            Some((_, edge_id)) => Err(EdgeDataError::MissingWeight { edge_id }),
+            None => Ok(()),
         }
-        Ok(neighbors)
+    }
+
+    /// Scans every edge for the targets of `node` and the edge weights.
+    ///
+    /// Prefer [`crate::Graph::neighbors_with_weights`], which answers from an
+    /// adjacency index. Missing weights follow the [`MissingWeight`] policy.
+    pub fn neighbors_with_weights(&self, node: NodeId) -> Result<Vec<(NodeId, Weight)>> {
+        let sources = self.source_ids()?;
+        let targets = self.target_ids()?;
+        let weights = self.resolved_weights()?;
+        Ok(sources
+            .values()
+            .zip(targets.values())
+            .zip(weights)
+            .filter(|((source_id, _), _)| *source_id == node)
+            .filter_map(|((_, target_id), weight)| Some((target_id, weight?)))
+            .collect())
+    }
+
+    /// Returns `(source, target, weight, edge row, edge id)` for every edge,
+    /// resolving missing weights by the [`MissingWeight`] policy.
+    fn index_entries(
+        &self,
+    ) -> Result<impl Iterator<Item = (NodeId, NodeId, Option<Weight>, usize, EdgeId)> + Clone + '_>
+    {
+        let sources = self.source_ids()?;
+        let targets = self.target_ids()?;
+        let weights = self.resolved_weights()?;
+        let edge_ids = self.edge_ids()?;
+        Ok(sources
+            .values()
+            .zip(targets.values())
+            .zip(weights)
+            .zip(edge_ids.values())
+            .enumerate()
+            .map(|(idx, (((source_id, target_id), weight), edge_id))| {
+                (source_id, target_id, weight, idx, edge_id)
+            }))
+    }
+
+    /// Builds the outgoing adjacency index of the edges.
+    pub fn adjacency_index(&self) -> Result<AdjacencyIndex> {
+        Ok(AdjacencyIndex::from_entries(self.index_entries()?))
+    }
+
+    /// Builds an adjacency index that follows every edge in both directions.
+    pub fn undirected_adjacency_index(&self) -> Result<AdjacencyIndex> {
+        let entries = self.index_entries()?;
+        Ok(AdjacencyIndex::from_entries(
+            entries.clone().chain(
+                entries
+                    // Self-loops are only indexed once.
+                    .filter(|(source_id, target_id, ..)| source_id != target_id)
+                    .map(|(source_id, target_id, weight, idx, edge_id)| {
+                        (target_id, source_id, weight, idx, edge_id)
+                    }),
+            ),
+        ))
+    }
+
+    /// Builds the incoming adjacency index of the edges, keyed by target.
+    pub fn reverse_adjacency_index(&self) -> Result<AdjacencyIndex> {
+        Ok(AdjacencyIndex::from_entries(self.index_entries()?.map(
+            |(source_id, target_id, weight, idx, edge_id)| {
+                (target_id, source_id, weight, idx, edge_id)
+            },
+        )))
     }
 
     pub fn edge(&self, idx: usize) -> Result<Edge> {
         if idx >= self.num_edges() {
             return Err(EdgeDataError::IndexOutOfBounds);
         }
+        let id = self.edge_id(idx)?;
         let source_id = self.source_id(idx)?;
         let target_id = self.target_id(idx)?;
         let weight = self.weight(idx)?;
-        Ok(Edge { source_id, target_id, weight })
+        let EdgeTime { timestamp, valid_from, valid_to } = self.time(idx)?;
+        let edge_type = self.edge_type(idx)?.map(Arc::from);
+        Ok(Edge {
+            id: Some(id),
+            source_id,
+            target_id,
+            weight,
+            timestamp,
+            valid_from,
+            valid_to,
+            edge_type,
+        })
+    }
+
+    /// Iterates over the edges in row order, downcasting each column once.
+    pub fn edges_iter(&self) -> Result<impl Iterator<Item = Edge> + '_> {
+        let edge_ids = self.edge_ids()?;
+        let sources = self.source_ids()?;
+        let targets = self.target_ids()?;
+        let weights = self.weights()?;
+        let times = self.times_iter()?;
+        let edge_types = self.edge_types_iter()?;
+        Ok(edge_ids
+            .values()
+            .zip(sources.values())
+            .zip(targets.values())
+            .zip(weights.iter())
+            .zip(times)
+            .zip(edge_types)
+            .map(|(((((id, source_id), target_id), weight), time), edge_type)| Edge {
+                id: Some(id),
+                source_id,
+                target_id,
+                weight,
+                timestamp: time.timestamp,
+                valid_from: time.valid_from,
+                valid_to: time.valid_to,
+                edge_type,
+            }))
     }
+
     pub fn edges(&self) -> Result<Vec<Edge>> {
-        let mut edges = Vec::with_capacity(self.num_edges());
-        for idx in 0..self.num_edges() {
-            let edge = self.edge(idx)?;
-            edges.push(edge);
+        Ok(self.edges_iter()?.collect())
+    }
+
+    /// Source ids of every edge, borrowed from the Arrow buffer.
+    ///
+    /// Fails if the edges span more than one chunk; see [`Self::compact`].
+    pub fn sources_slice(&self) -> Result<&[NodeId]> {
+        self.single_slice(self.source_ids()?)
+    }
+
+    /// Target ids of every edge, borrowed from the Arrow buffer.
+    ///
+    /// Fails if the edges span more than one chunk; see [`Self::compact`].
+    pub fn targets_slice(&self) -> Result<&[NodeId]> {
+        self.single_slice(self.target_ids()?)
+    }
+
+    fn single_slice<'a, N: ArrowPrimitiveType>(
+        &self,
+        column: ChunkedColumn<'a, PrimitiveArray<N>>,
+    ) -> Result<&'a [N::Native]> {
+        column
+            .as_slice()
+            .ok_or(EdgeDataError::MultipleChunks { num_chunks: self.record_batches().len() })
+    }
+
+    /// Fails if two edges share an id.
+    pub(crate) fn check_unique_ids(&self) -> Result<()> {
+        let mut edge_ids = HashSet::with_capacity(self.num_edges());
+        match self.edge_ids()?.values().find(|id| !edge_ids.insert(*id)) {
+            Some(edge_id) => Err(EdgeDataError::DuplicateEdgeId { edge_id }),
+            None => Ok(()),
         }
-        Ok(edges)
     }
 
+    /// Id after the largest edge id in use or previously removed.
+    pub fn next_edge_id(&self) -> Result<EdgeId> {
+        let next_id = match self.edge_ids()?.values().max() {
+            Some(id) => id.checked_add(1).ok_or(EdgeDataError::EdgeIdOverflow)?,
+            None => 0,
+        };
+        let removed_next_id = self
+            .schema_ref()
+            .metadata()
+            .get(NEXT_EDGE_ID_METADATA_KEY)
+            .and_then(|next_id| next_id.parse().ok())
+            .unwrap_or_default();
+        Ok(next_id.max(removed_next_id))
+    }
+
+    /// Appends edges, assigning ids after the largest existing id to edges
+    /// without one. Property columns are null for the new edges.
     pub fn add_edges(&mut self, edges: Vec<Edge>) -> Result<()> {
-        let record_batch = self.record_batch().clone();
-        let source_column = record_batch
-            .column_by_name(Attribute::Source.as_ref())
-            .ok_or(EdgeDataError::ColumnNotFound)?;
-        let target_column = record_batch
-            .column_by_name(Attribute::Target.as_ref())
-            .ok_or(EdgeDataError::ColumnNotFound)?;
-        let weight_column = record_batch
-            .column_by_name(Attribute::Weight.as_ref())
-            .ok_or(EdgeDataError::ColumnNotFound)?;
-        let source_data = source_column
-            .as_any()
-            .downcast_ref::<PrimitiveArray<UInt32Type>>()
-            .ok_or(EdgeDataError::ColumnTypeMismatch {
-                data_type: source_column.data_type().to_string(),
-            })?;
-        let target_data = target_column
-            .as_any()
-            .downcast_ref::<PrimitiveArray<UInt32Type>>()
-            .ok_or(EdgeDataError::ColumnTypeMismatch {
-                data_type: target_column.data_type().to_string(),
-            })?;
-        let weight_data = weight_column
-            .as_any()
-            .downcast_ref::<PrimitiveArray<Float64Type>>()
-            .ok_or(EdgeDataError::ColumnTypeMismatch {
-                data_type: weight_column.data_type().to_string(),
-            })?;
-        let new_source = source_data
-            .values()
-            .to_vec()
-            .into_iter()
-            .chain(edges.iter().map(|edge| edge.source_id))
-            .collect::<Vec<_>>();
-        let new_target = target_data
-            .values()
-            .to_vec()
-            .into_iter()
-            .chain(edges.iter().map(|edge| edge.target_id))
-            .collect::<Vec<_>>();
-        let new_weight = weight_data
-            .values()
-            .to_vec()
-            .into_iter()
-            .chain(edges.iter().map(|edge| edge.weight.unwrap_or_default()))
-            .collect::<Vec<_>>();
-        let new_edges = RecordBatch::try_new(
-            self.record_batch().schema(),
-            vec![
-                Arc::new(arrow::array::UInt32Array::from(new_source)),
-                Arc::new(arrow::array::UInt32Array::from(new_target)),
-                Arc::new(arrow::array::Float64Array::from(new_weight)),
-            ],
-        )
-        .context(FailedToAddEdgesSnafu {})?;
+        let next_id = self.next_edge_id()?;
+        self.add_edges_from_id(&edges, next_id)
+    }
+
+    /// Appends edges as a new chunk, numbering edges without an id from
+    /// `next_id`. Fails if an explicit id is already in use.
+    pub(crate) fn add_edges_from_id(&mut self, edges: &[Edge], next_id: EdgeId) -> Result<()> {
+        if edges.iter().any(|edge| edge.id.is_some()) {
+            let mut edge_ids = self.edge_ids()?.values().collect::<HashSet<_>>();
+            if let Some(edge_id) =
+                edges.iter().filter_map(|edge| edge.id).find(|id| !edge_ids.insert(*id))
+            {
+                return Err(EdgeDataError::DuplicateEdgeId { edge_id });
+            }
+        }
+        let new_edges = edges_to_record_batch(edges, next_id)?;
+        self.0.push(new_edges).context(FailedToAddEdgesSnafu {})
+    }
+
+    /// Appends the chunks of `other`.
+    pub(crate) fn append(&mut self, other: &Self) -> Result<(), ArrowError> {
+        other.record_batches().iter().try_for_each(|chunk| self.0.push(chunk.clone()))
+    }
 
-        self.0 = new_edges;
+    /// Numbers the edges in row order from `next_id`.
+    pub(crate) fn renumber(&mut self, next_id: EdgeId) -> Result<(), ArrowError> {
+        let idx = self.schema_ref().index_of(Attribute::Id.as_ref())?;
+        let mut next_id = next_id;
+        let ids = self
+            .record_batches()
+            .iter()
+            .map(|chunk| {
+                let end_id = next_id + chunk.num_rows() as EdgeId;
+                let ids = PrimitiveArray::<UInt64Type>::from_iter_values(next_id..end_id);
+                next_id = end_id;
+                Arc::new(ids) as ArrayRef
+            })
+            .collect();
+        self.0.replace_column(idx, ids)
+    }
+
+    /// Returns the edges with their sources and targets swapped, sharing the
+    /// column buffers.
+    pub(crate) fn reversed(&self) -> Result<Self, ArrowError> {
+        let source_idx = self.schema_ref().index_of(Attribute::Source.as_ref())?;
+        let target_idx = self.schema_ref().index_of(Attribute::Target.as_ref())?;
+        let column = |idx: usize| {
+            self.record_batches().iter().map(|chunk| chunk.column(idx).clone()).collect::<Vec<_>>()
+        };
+        let mut reversed = self.0.clone();
+        reversed.replace_column(source_idx, column(target_idx))?;
+        reversed.replace_column(target_idx, column(source_idx))?;
+        Ok(Self(reversed))
+    }
+
+    /// Replaces both ends of every edge with `map` of them.
+    pub(crate) fn map_ends(&mut self, map: impl Fn(NodeId) -> NodeId) -> Result<()> {
+        for attribute in [Attribute::Source, Attribute::Target] {
+            let ends = self
+                .column::<NodeIdArray>(attribute.as_ref())?
+                .chunks()
+                .iter()
+                .map(|ends| Arc::new(ends.unary::<_, NodeIdType>(&map)) as ArrayRef)
+                .collect();
+            let idx = self
+                .schema_ref()
+                .index_of(attribute.as_ref())
+                .map_err(|_| EdgeDataError::ColumnNotFound)?;
+            self.0.replace_column(idx, ends).context(FailedToAddEdgesSnafu {})?;
+        }
         Ok(())
     }
+
+    /// Keeps the edges where `keep` is true.
+    pub(crate) fn filter(&self, keep: &BooleanArray) -> Result<Self, ArrowError> {
+        Ok(Self(self.0.filter(keep)?))
+    }
+
+    /// Replaces the weights of the edges with the given ids.
+    pub fn set_weights(&mut self, weights: &HashMap<EdgeId, Weight>) -> Result<()> {
+        let new_weights = self
+            .edge_ids()?
+            .chunks()
+            .iter()
+            .zip(self.weights()?.chunks())
+            .map(|(ids, chunk_weights)| {
+                let chunk_weights = ids
+                    .values()
+                    .iter()
+                    .zip(chunk_weights.iter())
+                    .map(|(id, weight)| weights.get(id).copied().or(weight))
+                    .collect::<PrimitiveArray<Float64Type>>();
+                Arc::new(chunk_weights) as ArrayRef
+            })
+            .collect();
+        let (idx, _) = self
+            .schema_ref()
+            .column_with_name(Attribute::Weight.as_ref())
+            .ok_or(EdgeDataError::ColumnNotFound)?;
+        self.0.replace_column(idx, new_weights).context(FailedToAddEdgesSnafu {})
+    }
+}
+
+impl TryFrom<EdgeColumns> for EdgeRecordBatch {
+    type Error = EdgeDataError;
+
+    fn try_from(columns: EdgeColumns) -> Result<Self> {
+        Ok(Self(columns.into_record_batch(0)?.into()))
+    }
 }
 
 impl From<RecordBatch> for EdgeRecordBatch {
     fn from(record_batch: RecordBatch) -> Self {
-        Self(record_batch)
+        Self(record_batch.into())
     }
 }
 
-impl From<Vec<Edge>> for EdgeRecordBatch {
-    fn from(edges: Vec<Edge>) -> Self {
-        let mut source_id = Vec::new();
-        let mut target_id = Vec::new();
-        let mut weight = Vec::new();
+/// Core edge columns, filled one edge at a time. The time and edge type
+/// columns are only allocated once some edge has a value for them.
+#[derive(Debug, Default)]
+pub(crate) struct EdgeColumns {
+    ids: Vec<Option<EdgeId>>,
+    sources: Vec<NodeId>,
+    targets: Vec<NodeId>,
+    weights: Vec<Option<Weight>>,
+    timestamps: Option<Vec<Option<Timestamp>>>,
+    valid_from: Option<Vec<Option<Timestamp>>>,
+    valid_to: Option<Vec<Option<Timestamp>>>,
+    edge_types: Option<Vec<Option<Arc<str>>>>,
+}
+
+impl EdgeColumns {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            ids: Vec::with_capacity(capacity),
+            sources: Vec::with_capacity(capacity),
+            targets: Vec::with_capacity(capacity),
+            weights: Vec::with_capacity(capacity),
+            ..Default::default()
+        }
+    }
+
+    /// Pushes `value` onto an optional column, allocating it on the first value.
+    fn push_optional<T: Clone>(
+        column: &mut Option<Vec<Option<T>>>,
+        num_rows: usize,
+        value: Option<T>,
+    ) {
+        match (column.as_mut(), value) {
+            (Some(column), value) => column.push(value),
+            (None, Some(value)) => {
+                let mut values = vec![None; num_rows];
+                values.push(Some(value));
+                *column = Some(values);
+            }
+            (None, None) => {}
+        }
+    }
+
+    pub(crate) fn push(&mut self, edge: Edge) {
+        let num_rows = self.ids.len();
+        Self::push_optional(&mut self.timestamps, num_rows, edge.timestamp);
+        Self::push_optional(&mut self.valid_from, num_rows, edge.valid_from);
+        Self::push_optional(&mut self.valid_to, num_rows, edge.valid_to);
+        Self::push_optional(&mut self.edge_types, num_rows, edge.edge_type);
+        self.ids.push(edge.id);
+        self.sources.push(edge.source_id);
+        self.targets.push(edge.target_id);
+        self.weights.push(edge.weight);
+    }
+
+    pub(crate) fn sources(&self) -> &[NodeId] {
+        &self.sources
+    }
+
+    pub(crate) fn targets(&self) -> &[NodeId] {
+        &self.targets
+    }
+
+    /// Numbers edges without an id from `next_id` or from past the largest
+    /// explicit id, whichever is greater. Fails if the numbers run past
+    /// [`EdgeId::MAX`].
+    fn assign_ids(ids: Vec<Option<EdgeId>>, next_id: EdgeId) -> Result<Vec<EdgeId>> {
+        // `None` once every id is taken
+        let mut next_id = match ids.iter().flatten().max() {
+            Some(id) => id.checked_add(1).map(|id| id.max(next_id)),
+            None => Some(next_id),
+        };
+        ids.into_iter()
+            .map(|id| match id {
+                Some(id) => Ok(id),
+                None => {
+                    let id = next_id.ok_or(EdgeDataError::EdgeIdOverflow)?;
+                    next_id = id.checked_add(1);
+                    Ok(id)
+                }
+            })
+            .collect()
+    }
+
+    /// Builds a record batch with the core edge columns and the allocated
+    /// time columns, moving the column vectors into the Arrow buffers.
+    pub(crate) fn into_record_batch(self, next_id: EdgeId) -> Result<RecordBatch> {
+        let ids = Self::assign_ids(self.ids, next_id)?;
+        let mut fields = EdgeRecordBatch::schema().fields().to_vec();
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(PrimitiveArray::<UInt64Type>::from(ids)),
+            Arc::new(NodeIdArray::from(self.sources)),
+            Arc::new(NodeIdArray::from(self.targets)),
+            Arc::new(PrimitiveArray::<Float64Type>::from(self.weights)),
+        ];
+        for (attribute, values) in [
+            (Attribute::Timestamp, self.timestamps),
+            (Attribute::ValidFrom, self.valid_from),
+            (Attribute::ValidTo, self.valid_to),
+        ] {
+            if let Some(values) = values {
+                fields.push(Arc::new(Field::new(attribute.as_ref(), DataType::Int64, true)));
+                columns.push(Arc::new(PrimitiveArray::<Int64Type>::from(values)));
+            }
+        }
+        if let Some(edge_types) = self.edge_types {
+            let data_type = property::dictionary_string_type();
+            fields.push(Arc::new(Field::new(Attribute::EdgeType.as_ref(), data_type, true)));
+            let edge_types =
+                edge_types.iter().map(Option::as_deref).collect::<DictionaryStringArray>();
+            columns.push(Arc::new(edge_types));
+        }
+        Ok(RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap())
+    }
+}
 
+impl Extend<Edge> for EdgeColumns {
+    fn extend<I: IntoIterator<Item = Edge>>(&mut self, edges: I) {
         for edge in edges {
-            source_id.push(edge.source_id);
-            target_id.push(edge.target_id);
-            weight.push(edge.weight.unwrap_or_default());
+            self.push(edge);
         }
+    }
+}
 
-        let record_batch = RecordBatch::try_new(
-            Self::schema().into(),
-            vec![
-                Arc::new(PrimitiveArray::<UInt32Type>::from(source_id)),
-                Arc::new(PrimitiveArray::<UInt32Type>::from(target_id)),
-                Arc::new(PrimitiveArray::<Float64Type>::from(weight)),
-            ],
-        )
-        .unwrap();
+impl FromIterator<Edge> for EdgeColumns {
+    fn from_iter<I: IntoIterator<Item = Edge>>(edges: I) -> Self {
+        let edges = edges.into_iter();
+        let mut columns = Self::with_capacity(edges.size_hint().0);
+        columns.extend(edges);
+        columns
+    }
+}
+
+/// Builds a record batch with the core edge columns.
+fn edges_to_record_batch(edges: &[Edge], next_id: EdgeId) -> Result<RecordBatch> {
+    edges.iter().cloned().collect::<EdgeColumns>().into_record_batch(next_id)
+}
+
+/// # Panics
+///
+/// Panics if an edge without an id follows an edge with id [`EdgeId::MAX`].
+/// [`crate::Graph::from_edges`] returns an error instead.
+impl From<Vec<Edge>> for EdgeRecordBatch {
+    fn from(edges: Vec<Edge>) -> Self {
+        Self(edges_to_record_batch(&edges, 0).expect("edge ids overflow").into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Graph, GraphError};
+
+    #[test]
+    fn test_add_edges_assigns_ids() {
+        let mut edge_record_batch = EdgeRecordBatch::from(vec![
+            Edge::builder().source_id(1).target_id(2).build().unwrap(),
+            Edge::builder().source_id(2).target_id(3).build().unwrap(),
+        ]);
+        edge_record_batch
+            .add_edges(vec![Edge::builder().source_id(1).target_id(2).build().unwrap()])
+            .unwrap();
+        assert_eq!(
+            edge_record_batch.edge_ids().unwrap().values().collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+
+        // Appends add a chunk, which compaction merges back.
+        assert_eq!(edge_record_batch.record_batches().len(), 2);
+        assert_eq!(edge_record_batch.edge(2).unwrap().id, Some(2));
+        let chunked = edge_record_batch.clone();
+        edge_record_batch.compact();
+        assert_eq!(edge_record_batch.record_batches().len(), 1);
+        assert_eq!(edge_record_batch, chunked);
+    }
+
+    #[test]
+    fn test_duplicate_edge_ids() {
+        let edges = vec![
+            Edge::builder().id(5u64).source_id(1).target_id(2).build().unwrap(),
+            Edge::builder().id(5u64).source_id(2).target_id(3).build().unwrap(),
+        ];
+        assert!(matches!(
+            Graph::builder().edges(edges).build(),
+            Err(GraphError::EdgeData { source: EdgeDataError::DuplicateEdgeId { edge_id: 5 } })
+        ));
 
-        Self(record_batch)
+        let mut edge_record_batch = EdgeRecordBatch::from(vec![Edge::builder()
+            .id(5u64)
+            .source_id(1)
+            .target_id(2)
+            .build()
+            .unwrap()]);
+        let duplicate = Edge::builder().id(5u64).source_id(2).target_id(1).build().unwrap();
+        assert!(matches!(
+            edge_record_batch.add_edges(vec![duplicate]),
+            Err(EdgeDataError::DuplicateEdgeId { edge_id: 5 })
+        ));
+        let twice = Edge::builder().id(6u64).source_id(2).target_id(1).build().unwrap();
+        assert!(edge_record_batch.add_edges(vec![twice.clone(), twice]).is_err());
+        assert_eq!(edge_record_batch.num_edges(), 1);
     }
 }
@@ -0,0 +1,472 @@
+//! Random and structured graph generators.
+//!
+//! Generated graphs have the nodes `0..num_nodes` and edges of unit weight.
+//! Random generators draw from the given `rng`, so seeding it makes them
+//! reproducible. Node counts that do not fit a [`NodeId`] are rejected up
+//! front, so node indexes below them convert to node ids losslessly.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashSet},
+};
+
+use rand::Rng;
+use snafu::prelude::*;
+
+use crate::{
+    edge::EdgeRecordBatch,
+    graph::EdgeDataSnafu,
+    node::{Node, NodeRecordBatch},
+    types::NodeId,
+    Graph, GraphError, GraphKind,
+};
+
+/// Builds the graph of the nodes `0..num_nodes` and the `edges`.
+fn graph(
+    num_nodes: NodeId,
+    edges: Vec<(NodeId, NodeId)>,
+    kind: GraphKind,
+) -> Result<Graph, GraphError> {
+    let weights = vec![1.0; edges.len()];
+    let (sources, targets) = edges.into_iter().unzip();
+    let node_ids = (0..num_nodes).collect();
+    Graph::from_node_ids_and_edge_columns(node_ids, sources, targets, weights, kind)
+}
+
+fn invalid(name: &str) -> GraphError {
+    GraphError::InvalidParameter { name: name.to_string() }
+}
+
+/// Converts a node count to a node id, failing with the parameter `name` if
+/// it does not fit.
+fn num_node_ids(num_nodes: usize, name: &str) -> Result<NodeId, GraphError> {
+    NodeId::try_from(num_nodes).map_err(|_| invalid(name))
+}
+
+/// Number of node pairs an edge can join, without self-loops.
+fn num_pairs(num_nodes: usize, kind: GraphKind) -> usize {
+    let ordered = num_nodes * num_nodes.saturating_sub(1);
+    match kind {
+        GraphKind::Directed => ordered,
+        GraphKind::Undirected => ordered / 2,
+    }
+}
+
+/// Erdős–Rényi graph G(n, p), joining each pair of distinct nodes with
+/// probability `p`.
+///
+/// Runs in time linear in the number of nodes and edges, skipping the pairs
+/// left out instead of drawing for each of them.
+pub fn gnp(
+    num_nodes: usize,
+    p: f64,
+    kind: GraphKind,
+    rng: &mut impl Rng,
+) -> Result<Graph, GraphError> {
+    let num_node_ids = num_node_ids(num_nodes, "num_nodes")?;
+    if !(0.0..=1.0).contains(&p) {
+        return Err(invalid("p"));
+    }
+    let num_pairs = num_pairs(num_nodes, kind);
+    let mut edges = Vec::new();
+    if p > 0.0 {
+        // Pairs are numbered in order, and the gap to the next kept pair is
+        // geometrically distributed.
+        let log_q = (1.0 - p).ln();
+        let mut pair = 0;
+        loop {
+            if p < 1.0 {
+                let gap = ((1.0 - rng.random::<f64>()).ln() / log_q).floor();
+                if gap >= (num_pairs - pair) as f64 {
+                    break;
+                }
+                pair += gap as usize;
+            }
+            if pair >= num_pairs {
+                break;
+            }
+            edges.push(nth_pair(pair, num_nodes, kind));
+            pair += 1;
+        }
+    }
+    graph(num_node_ids, edges, kind)
+}
+
+/// Returns the `pair`-th pair of distinct nodes, ordered by source. Undirected
+/// pairs have the larger node first.
+fn nth_pair(pair: usize, num_nodes: usize, kind: GraphKind) -> (NodeId, NodeId) {
+    match kind {
+        GraphKind::Directed => {
+            let (source, target) = (pair / (num_nodes - 1), pair % (num_nodes - 1));
+            let target = target + (target >= source) as usize;
+            (source as NodeId, target as NodeId)
+        }
+        GraphKind::Undirected => {
+            // Node `source` is preceded by source * (source - 1) / 2 pairs.
+            let mut source = (((8 * pair + 1) as f64).sqrt() as usize + 1) / 2;
+            while source * (source - 1) / 2 > pair {
+                source -= 1;
+            }
+            while (source + 1) * source / 2 <= pair {
+                source += 1;
+            }
+            (source as NodeId, (pair - source * (source - 1) / 2) as NodeId)
+        }
+    }
+}
+
+/// Erdős–Rényi graph G(n, m), with `num_edges` edges between distinct pairs
+/// of distinct nodes drawn uniformly.
+pub fn gnm(
+    num_nodes: usize,
+    num_edges: usize,
+    kind: GraphKind,
+    rng: &mut impl Rng,
+) -> Result<Graph, GraphError> {
+    let num_node_ids = num_node_ids(num_nodes, "num_nodes")?;
+    let num_pairs = num_pairs(num_nodes, kind);
+    if num_edges > num_pairs {
+        return Err(invalid("num_edges"));
+    }
+    // Dense graphs draw the pairs left out instead.
+    let dense = num_edges > num_pairs / 2;
+    let num_drawn = if dense { num_pairs - num_edges } else { num_edges };
+    let mut drawn = HashSet::with_capacity(num_drawn);
+    while drawn.len() < num_drawn {
+        drawn.insert(rng.random_range(0..num_pairs));
+    }
+    let mut pairs = match dense {
+        true => (0..num_pairs).filter(|pair| !drawn.contains(pair)).collect::<Vec<_>>(),
+        false => drawn.into_iter().collect(),
+    };
+    pairs.sort_unstable();
+    let edges = pairs.into_iter().map(|pair| nth_pair(pair, num_nodes, kind)).collect();
+    graph(num_node_ids, edges, kind)
+}
+
+/// Barabási–Albert preferential attachment graph, undirected.
+///
+/// Each node after the first `num_attached` joins `num_attached` distinct
+/// earlier nodes, drawn in proportion to their degree.
+pub fn barabasi_albert(
+    num_nodes: usize,
+    num_attached: usize,
+    rng: &mut impl Rng,
+) -> Result<Graph, GraphError> {
+    let num_node_ids = num_node_ids(num_nodes, "num_nodes")?;
+    if num_attached == 0 || num_attached >= num_nodes {
+        return Err(invalid("num_attached"));
+    }
+    let mut edges = Vec::with_capacity((num_nodes - num_attached) * num_attached);
+    // Every node appears once per edge end, so uniform draws from it are
+    // proportional to degree.
+    let mut ends = Vec::with_capacity(2 * edges.capacity());
+    let mut targets = (0..num_attached as NodeId).collect::<Vec<_>>();
+    for source in num_attached as NodeId..num_node_ids {
+        for &target in &targets {
+            edges.push((source, target));
+            ends.extend([source, target]);
+        }
+        let mut chosen = HashSet::with_capacity(num_attached);
+        while chosen.len() < num_attached {
+            chosen.insert(ends[rng.random_range(0..ends.len())]);
+        }
+        targets = chosen.into_iter().collect();
+        targets.sort_unstable();
+    }
+    graph(num_node_ids, edges, GraphKind::Undirected)
+}
+
+/// Watts–Strogatz small-world graph, undirected.
+///
+/// Starts from a ring joining each node to its `num_neighbors / 2` nearest
+/// neighbors on either side, then rewires the far end of each edge to a
+/// uniformly drawn node with probability `beta`, avoiding self-loops and
+/// parallel edges.
+pub fn watts_strogatz(
+    num_nodes: usize,
+    num_neighbors: usize,
+    beta: f64,
+    rng: &mut impl Rng,
+) -> Result<Graph, GraphError> {
+    let num_node_ids = num_node_ids(num_nodes, "num_nodes")?;
+    if num_neighbors < 2 || num_neighbors >= num_nodes {
+        return Err(invalid("num_neighbors"));
+    }
+    if !(0.0..=1.0).contains(&beta) {
+        return Err(invalid("beta"));
+    }
+    let mut edges = Vec::with_capacity(num_nodes * (num_neighbors / 2));
+    let mut present = HashSet::with_capacity(edges.capacity());
+    let mut degrees = vec![2 * (num_neighbors / 2); num_nodes];
+    for offset in 1..=num_neighbors / 2 {
+        for source in 0..num_nodes {
+            let edge = (source as NodeId, ((source + offset) % num_nodes) as NodeId);
+            edges.push(edge);
+            present.insert(ordered(edge));
+        }
+    }
+    for edge in edges.iter_mut() {
+        let source = edge.0;
+        // Nodes joined to every other node cannot be rewired.
+        if degrees[source as usize] + 1 >= num_nodes || !rng.random_bool(beta) {
+            continue;
+        }
+        let target = loop {
+            let target = rng.random_range(0..num_nodes) as NodeId;
+            if target != source && !present.contains(&ordered((source, target))) {
+                break target;
+            }
+        };
+        present.remove(&ordered(*edge));
+        present.insert(ordered((source, target)));
+        degrees[edge.1 as usize] -= 1;
+        degrees[target as usize] += 1;
+        edge.1 = target;
+    }
+    graph(num_node_ids, edges, GraphKind::Undirected)
+}
+
+fn ordered((a, b): (NodeId, NodeId)) -> (NodeId, NodeId) {
+    (a.min(b), a.max(b))
+}
+
+/// Stochastic block model graph.
+///
+/// Nodes are split into consecutive blocks of `block_sizes`, and each pair of
+/// distinct nodes is joined with the probability at the row of the source
+/// block and the column of the target block of the row-major `probabilities`.
+/// Undirected graphs read the upper triangle.
+pub fn stochastic_block_model(
+    block_sizes: &[usize],
+    probabilities: &[f64],
+    kind: GraphKind,
+    rng: &mut impl Rng,
+) -> Result<Graph, GraphError> {
+    let num_blocks = block_sizes.len();
+    if probabilities.len() != num_blocks * num_blocks
+        || probabilities.iter().any(|p| !(0.0..=1.0).contains(p))
+    {
+        return Err(invalid("probabilities"));
+    }
+    let num_nodes = block_sizes
+        .iter()
+        .try_fold(0usize, |num_nodes, &size| num_nodes.checked_add(size))
+        .ok_or_else(|| invalid("block_sizes"))?;
+    let num_node_ids = num_node_ids(num_nodes, "block_sizes")?;
+    let blocks = block_sizes
+        .iter()
+        .enumerate()
+        .flat_map(|(block, &size)| std::iter::repeat_n(block, size))
+        .collect::<Vec<_>>();
+    let mut edges = Vec::new();
+    for source in 0..num_nodes {
+        let first_target = match kind {
+            GraphKind::Directed => 0,
+            GraphKind::Undirected => source + 1,
+        };
+        for target in (first_target..num_nodes).filter(|&target| target != source) {
+            let (row, column) = (blocks[source], blocks[target]);
+            let (row, column) = match kind {
+                GraphKind::Directed => (row, column),
+                GraphKind::Undirected => (row.min(column), row.max(column)),
+            };
+            if rng.random_bool(probabilities[row * num_blocks + column]) {
+                edges.push((source as NodeId, target as NodeId));
+            }
+        }
+    }
+    graph(num_node_ids, edges, kind)
+}
+
+/// Two-dimensional grid of `rows` by `columns` nodes, undirected.
+///
+/// Node `row * columns + column` is positioned at `(column, row)` and joined
+/// to the nodes to its right and below.
+pub fn grid(rows: usize, columns: usize) -> Result<Graph, GraphError> {
+    let num_nodes = rows.checked_mul(columns).ok_or_else(|| invalid("rows"))?;
+    num_node_ids(num_nodes, "rows")?;
+    let nodes = (0..num_nodes)
+        .map(|id| Node {
+            id: id as NodeId,
+            key: None,
+            weight: None,
+            position: Some(((id % columns) as f64, (id / columns) as f64).into()),
+            label: None,
+        })
+        .collect::<Vec<_>>();
+    let mut sources = Vec::new();
+    let mut targets = Vec::new();
+    for id in 0..num_nodes {
+        if id % columns + 1 < columns {
+            sources.push(id as NodeId);
+            targets.push((id + 1) as NodeId);
+        }
+        if id + columns < num_nodes {
+            sources.push(id as NodeId);
+            targets.push((id + columns) as NodeId);
+        }
+    }
+    let weights = vec![1.0; sources.len()];
+    let edge_record_batch = EdgeRecordBatch::try_from_columns(sources, targets, Some(weights))
+        .context(EdgeDataSnafu {})?;
+    Graph::from_arrow_record_batches(
+        NodeRecordBatch::from(nodes),
+        edge_record_batch.with_kind(GraphKind::Undirected),
+    )
+}
+
+/// Complete graph, joining every pair of distinct nodes.
+pub fn complete(num_nodes: usize, kind: GraphKind) -> Result<Graph, GraphError> {
+    let num_node_ids = num_node_ids(num_nodes, "num_nodes")?;
+    let edges = (0..num_pairs(num_nodes, kind)).map(|pair| nth_pair(pair, num_nodes, kind));
+    graph(num_node_ids, edges.collect(), kind)
+}
+
+/// Star graph with edges from node 0 to every other node.
+pub fn star(num_nodes: usize, kind: GraphKind) -> Result<Graph, GraphError> {
+    let num_node_ids = num_node_ids(num_nodes, "num_nodes")?;
+    let edges = (1..num_node_ids).map(|leaf| (0, leaf)).collect();
+    graph(num_node_ids, edges, kind)
+}
+
+/// Path graph with edges from each node to the next.
+pub fn path(num_nodes: usize, kind: GraphKind) -> Result<Graph, GraphError> {
+    let num_node_ids = num_node_ids(num_nodes, "num_nodes")?;
+    let edges = (1..num_node_ids).map(|node| (node - 1, node)).collect();
+    graph(num_node_ids, edges, kind)
+}
+
+/// Cycle graph with edges from each node to the next, and from the last
+/// node back to node 0.
+pub fn cycle(num_nodes: usize, kind: GraphKind) -> Result<Graph, GraphError> {
+    let num_node_ids = num_node_ids(num_nodes, "num_nodes")?;
+    let mut edges = (1..num_node_ids).map(|node| (node - 1, node)).collect::<Vec<_>>();
+    if num_node_ids > 1 {
+        edges.push((num_node_ids - 1, 0));
+    }
+    graph(num_node_ids, edges, kind)
+}
+
+/// Undirected tree drawn uniformly from the labelled trees on `num_nodes`
+/// nodes, by decoding a random Prüfer sequence.
+pub fn random_tree(num_nodes: usize, rng: &mut impl Rng) -> Result<Graph, GraphError> {
+    let num_node_ids = num_node_ids(num_nodes, "num_nodes")?;
+    if num_nodes < 2 {
+        return graph(num_node_ids, Vec::new(), GraphKind::Undirected);
+    }
+    let sequence = (0..num_nodes - 2).map(|_| rng.random_range(0..num_nodes)).collect::<Vec<_>>();
+    let mut degrees = vec![1; num_nodes];
+    for &node in &sequence {
+        degrees[node] += 1;
+    }
+    let mut leaves =
+        (0..num_nodes).filter(|&node| degrees[node] == 1).map(Reverse).collect::<BinaryHeap<_>>();
+    let mut edges = Vec::with_capacity(num_nodes - 1);
+    for node in sequence {
+        let Reverse(leaf) = leaves.pop().unwrap();
+        edges.push((node as NodeId, leaf as NodeId));
+        degrees[node] -= 1;
+        if degrees[node] == 1 {
+            leaves.push(Reverse(node));
+        }
+    }
+    let Reverse(a) = leaves.pop().unwrap();
+    let Reverse(b) = leaves.pop().unwrap();
+    edges.push((a as NodeId, b as NodeId));
+    graph(num_node_ids, edges, GraphKind::Undirected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GraphRef;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_random_generators() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let graph = gnp(50, 0.1, GraphKind::Directed, &mut rng).unwrap();
+        assert_eq!(graph.num_nodes(), 50);
+        assert!(graph.validate().unwrap().is_valid());
+        assert_eq!(gnp(6, 1.0, GraphKind::Undirected, &mut rng).unwrap().num_edges(), 15);
+        assert_eq!(gnp(6, 1.0, GraphKind::Directed, &mut rng).unwrap().num_edges(), 30);
+        assert_eq!(gnp(6, 0.0, GraphKind::Directed, &mut rng).unwrap().num_edges(), 0);
+        assert!(gnp(6, 1.5, GraphKind::Directed, &mut rng).is_err());
+
+        // Seeded generators are reproducible.
+        let a = gnm(40, 100, GraphKind::Undirected, &mut StdRng::seed_from_u64(1)).unwrap();
+        let b = gnm(40, 100, GraphKind::Undirected, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert_eq!(a.edge_record_batch(), b.edge_record_batch());
+        assert_eq!(a.num_edges(), 100);
+        assert!(a.validate().unwrap().is_valid());
+        assert_eq!(gnm(5, 19, GraphKind::Directed, &mut rng).unwrap().num_edges(), 19);
+        assert!(gnm(5, 11, GraphKind::Undirected, &mut rng).is_err());
+
+        let graph = barabasi_albert(100, 3, &mut rng).unwrap();
+        assert_eq!(graph.num_edges(), 97 * 3);
+        assert!(graph.is_connected());
+
+        let graph = watts_strogatz(30, 4, 0.3, &mut rng).unwrap();
+        assert_eq!(graph.num_edges(), 60);
+        assert!(graph.validate().unwrap().is_valid());
+
+        let graph = stochastic_block_model(
+            &[10, 10],
+            &[1.0, 0.0, 0.0, 1.0],
+            GraphKind::Undirected,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(graph.num_edges(), 90);
+        assert!(!graph.is_connected());
+
+        let graph = random_tree(40, &mut rng).unwrap();
+        assert_eq!(graph.num_edges(), 39);
+        assert!(graph.is_connected());
+        assert!(graph.is_acyclic());
+    }
+
+    #[test]
+    fn test_structured_generators() {
+        let graph = grid(3, 4).unwrap();
+        assert_eq!(graph.num_nodes(), 12);
+        assert_eq!(graph.num_edges(), 17);
+        assert_eq!(graph.neighbors(5).unwrap(), vec![1, 4, 6, 9]);
+        assert_eq!(graph.node_position_by_id(6).unwrap(), Some((2.0, 1.0).into()));
+
+        let graph = complete(5, GraphKind::Undirected).unwrap();
+        assert!(graph.is_complete());
+        assert!(complete(5, GraphKind::Directed).unwrap().is_complete());
+
+        let graph = star(5, GraphKind::Undirected).unwrap();
+        assert_eq!(graph.neighbors(0).unwrap(), vec![1, 2, 3, 4]);
+
+        let graph = path(4, GraphKind::Directed).unwrap();
+        assert!(graph.is_acyclic());
+        assert_eq!(GraphRef::node_count(&graph), 4);
+
+        let graph = cycle(4, GraphKind::Directed).unwrap();
+        assert!(!graph.is_acyclic());
+        assert_eq!(graph.neighbors(3).unwrap(), vec![0]);
+        assert_eq!(cycle(1, GraphKind::Directed).unwrap().num_edges(), 0);
+    }
+
+    #[test]
+    #[cfg(not(feature = "u64-node-ids"))]
+    fn test_too_many_nodes() {
+        let num_nodes = NodeId::MAX as usize + 1;
+        let mut rng = StdRng::seed_from_u64(7);
+        let is_invalid = |result: Result<Graph, GraphError>, name: &str| matches!(result, Err(GraphError::InvalidParameter { name: invalid }) if invalid == name);
+        assert!(is_invalid(star(num_nodes, GraphKind::Directed), "num_nodes"));
+        assert!(is_invalid(cycle(num_nodes, GraphKind::Directed), "num_nodes"));
+        assert!(is_invalid(gnm(num_nodes, 1, GraphKind::Directed, &mut rng), "num_nodes"));
+        assert!(is_invalid(random_tree(num_nodes, &mut rng), "num_nodes"));
+        assert!(is_invalid(grid(usize::MAX, 2), "rows"));
+        assert!(is_invalid(grid(num_nodes, 1), "rows"));
+        assert!(is_invalid(
+            stochastic_block_model(&[num_nodes], &[0.0], GraphKind::Directed, &mut rng),
+            "block_sizes"
+        ));
+    }
+}
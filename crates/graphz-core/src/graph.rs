@@ -1,25 +1,80 @@
-use arrow::array::RecordBatch;
+use arrow::{array::RecordBatch, datatypes::SchemaRef, error::ArrowError};
 use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
 use snafu::prelude::*;
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, OnceLock},
+};
 
 use crate::{
-    edge::{Edge, EdgeDataError, EdgeRecordBatch},
+    adjacency::AdjacencyIndex,
+    edge::{Edge, EdgeColumns, EdgeDataError, EdgeRecordBatch, KeyedEdge, MissingWeight},
     node::{Node, NodeDataError, NodeRecordBatch},
-    types::NodeId,
+    position::Position,
+    property::{has_required_fields, PropertyValue},
+    types::{EdgeId, NodeId, Weight},
+    validate::ValidationReport,
 };
 
 #[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
 pub enum GraphError {
     InvalidNodeSchema,
     InvalidEdgeSchema,
+    InvalidGraphKind { source: EdgeDataError },
     EmptyGraph { name: String },
+    NodeNotFound { node_id: NodeId },
+    DuplicateNodeId { node_id: NodeId },
+    NodeIdOverflow,
+    EdgeNotFound { edge_id: EdgeId },
+    NodeData { source: NodeDataError },
+    EdgeData { source: EdgeDataError },
+    FailedToEditGraph { source: ArrowError },
+    FailedToFilterGraph { source: ArrowError },
+    InvalidGraph { report: Box<ValidationReport> },
+    InvalidAdjacencyMatrix { len: usize, num_nodes: usize },
+    InvalidParameter { name: String },
+    MismatchedGraphKinds,
+    FailedToCombineGraphs { source: ArrowError },
+}
+
+/// Whether edges are followed from source to target only, or both ways.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::EnumString,
+    strum::AsRefStr,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphKind {
+    #[default]
+    #[strum(serialize = "directed")]
+    Directed,
+    #[strum(serialize = "undirected")]
+    Undirected,
 }
 
 #[derive(Debug)]
 pub struct Graph {
+    kind: GraphKind,
     node_record_batch: Arc<NodeRecordBatch>,
     edge_record_batch: Arc<EdgeRecordBatch>,
+    outgoing: OnceLock<AdjacencyIndex>,
+    incoming: OnceLock<AdjacencyIndex>,
+    edge_rows: OnceLock<HashMap<EdgeId, usize>>,
+    node_rows: OnceLock<HashMap<NodeId, usize>>,
+    node_keys: OnceLock<HashMap<String, NodeId>>,
+    // Concatenated chunks, kept across compaction since the rows are unchanged.
+    node_batch: OnceLock<Arc<RecordBatch>>,
+    edge_batch: OnceLock<Arc<RecordBatch>>,
 }
 
 impl Graph {
@@ -31,24 +86,130 @@ impl Graph {
         node_record_batch: NodeRecordBatch,
         edge_record_batch: EdgeRecordBatch,
     ) -> Result<Self, GraphError> {
-        if NodeRecordBatch::schema() != *node_record_batch.record_batch().schema() {
+        // Columns beyond the required ones are kept as properties.
+        let node_schema = node_record_batch.schema_ref();
+        if !has_required_fields(node_schema, &NodeRecordBatch::schema_with_dimension(2))
+            && !has_required_fields(node_schema, &NodeRecordBatch::schema_with_dimension(3))
+        {
             return Err(GraphError::InvalidNodeSchema);
         }
-        if EdgeRecordBatch::schema() != *edge_record_batch.record_batch().schema() {
+        if !has_required_fields(edge_record_batch.schema_ref(), &EdgeRecordBatch::schema()) {
             return Err(GraphError::InvalidEdgeSchema);
         }
+        let kind = edge_record_batch.kind().context(InvalidGraphKindSnafu {})?;
+        edge_record_batch.check_missing_weights().context(EdgeDataSnafu {})?;
+        edge_record_batch.check_unique_ids().context(EdgeDataSnafu {})?;
+        node_record_batch.check_unique_keys().context(NodeDataSnafu {})?;
         Ok(Self {
+            kind,
             node_record_batch: Arc::new(node_record_batch),
             edge_record_batch: Arc::new(edge_record_batch),
+            outgoing: OnceLock::new(),
+            incoming: OnceLock::new(),
+            edge_rows: OnceLock::new(),
+            node_rows: OnceLock::new(),
+            node_keys: OnceLock::new(),
+            node_batch: OnceLock::new(),
+            edge_batch: OnceLock::new(),
         })
     }
 
+    /// Builds a graph from a stream of edges, inferring the nodes from the
+    /// ends of the edges.
+    ///
+    /// Unlike [`GraphBuilder`], the edges are written straight into the edge
+    /// columns without being collected first.
+    pub fn from_edges(
+        edges: impl IntoIterator<Item = Edge>,
+        kind: GraphKind,
+    ) -> Result<Self, GraphError> {
+        let columns = edges.into_iter().collect::<EdgeColumns>();
+        let node_ids = infer_node_ids(columns.sources(), columns.targets());
+        Self::from_arrow_record_batches(
+            NodeRecordBatch::from_node_ids(node_ids),
+            EdgeRecordBatch::try_from(columns).context(EdgeDataSnafu {})?.with_kind(kind),
+        )
+    }
+
+    /// Builds a graph from edge columns, inferring the nodes from the ends of
+    /// the edges. The vectors are moved into the Arrow buffers without copying.
+    pub fn from_edge_columns(
+        sources: Vec<NodeId>,
+        targets: Vec<NodeId>,
+        weights: Option<Vec<Weight>>,
+        kind: GraphKind,
+    ) -> Result<Self, GraphError> {
+        let node_ids = infer_node_ids(&sources, &targets);
+        let edge_record_batch = EdgeRecordBatch::try_from_columns(sources, targets, weights)
+            .context(EdgeDataSnafu {})?;
+        Self::from_arrow_record_batches(
+            NodeRecordBatch::from_node_ids(node_ids),
+            edge_record_batch.with_kind(kind),
+        )
+    }
+
+    pub fn kind(&self) -> GraphKind {
+        self.kind
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.kind == GraphKind::Directed
+    }
+
+    /// How edges without a weight are treated by the adjacency indexes.
+    pub fn missing_weight(&self) -> Result<MissingWeight, EdgeDataError> {
+        self.edge_record_batch.missing_weight()
+    }
+
+    /// Returns the graph with `policy` for edges without a weight. Fails if
+    /// the policy is [`MissingWeight::Error`] and an edge has no weight.
+    pub fn with_missing_weight(&self, policy: MissingWeight) -> Result<Graph, GraphError> {
+        let edge_record_batch = self.edge_data().clone().with_missing_weight(policy);
+        Graph::from_arrow_record_batches(self.node_data().clone(), edge_record_batch)
+    }
+
+    /// Returns the nodes as one record batch, concatenating the chunks on
+    /// first use if there is more than one.
     pub fn node_record_batch(&self) -> Arc<RecordBatch> {
-        self.node_record_batch.record_batch().clone().into()
+        self.node_batch.get_or_init(|| self.node_record_batch.to_record_batch().into()).clone()
     }
 
+    /// Returns the edges as one record batch, concatenating the chunks on
+    /// first use if there is more than one.
     pub fn edge_record_batch(&self) -> Arc<RecordBatch> {
-        self.edge_record_batch.record_batch().clone().into()
+        self.edge_batch.get_or_init(|| self.edge_record_batch.to_record_batch().into()).clone()
+    }
+
+    pub fn node_schema(&self) -> SchemaRef {
+        self.node_record_batch.schema_ref().clone()
+    }
+
+    pub fn edge_schema(&self) -> SchemaRef {
+        self.edge_record_batch.schema_ref().clone()
+    }
+
+    pub fn node_record_batches(&self) -> &[RecordBatch] {
+        self.node_record_batch.record_batches()
+    }
+
+    pub fn edge_record_batches(&self) -> &[RecordBatch] {
+        self.edge_record_batch.record_batches()
+    }
+
+    pub(crate) fn node_data(&self) -> &NodeRecordBatch {
+        &self.node_record_batch
+    }
+
+    pub(crate) fn edge_data(&self) -> &EdgeRecordBatch {
+        &self.edge_record_batch
+    }
+
+    /// Merges the chunks of the nodes and edges, each into one record batch.
+    ///
+    /// Rows keep their order, so the indexes stay valid.
+    pub fn compact(&mut self) {
+        Arc::make_mut(&mut self.node_record_batch).compact();
+        Arc::make_mut(&mut self.edge_record_batch).compact();
     }
 
     pub fn num_nodes(&self) -> usize {
@@ -59,10 +220,187 @@ impl Graph {
         self.node_record_batch.node_id(idx)
     }
 
+    pub fn node(&self, idx: usize) -> Result<Node, NodeDataError> {
+        self.node_record_batch.node(idx)
+    }
+
+    pub fn node_position(&self, idx: usize) -> Result<Option<Position>, NodeDataError> {
+        self.node_record_batch.position(idx)
+    }
+
+    /// Iterates over the nodes without materializing them all.
+    pub fn nodes_iter(&self) -> Result<impl Iterator<Item = Node> + '_, NodeDataError> {
+        self.node_record_batch.nodes_iter()
+    }
+
+    /// Returns the row of the node with the given id, building the id index
+    /// on first use.
+    pub fn node_row(&self, node_id: NodeId) -> Result<Option<usize>, NodeDataError> {
+        let node_rows = match self.node_rows.get() {
+            Some(node_rows) => node_rows,
+            None => {
+                let node_ids = self.node_record_batch.node_ids()?;
+                let node_rows = node_ids.values().enumerate().map(|(idx, id)| (id, idx)).collect();
+                self.node_rows.get_or_init(|| node_rows)
+            }
+        };
+        Ok(node_rows.get(&node_id).copied())
+    }
+
+    pub fn contains_node(&self, node_id: NodeId) -> Result<bool, NodeDataError> {
+        Ok(self.node_row(node_id)?.is_some())
+    }
+
+    pub fn node_by_id(&self, node_id: NodeId) -> Result<Option<Node>, NodeDataError> {
+        self.node_row(node_id)?.map(|idx| self.node(idx)).transpose()
+    }
+
+    /// Returns the weight of the node with id `node_id`, or `None` if there is
+    /// no such node or it has no weight.
+    pub fn node_weight_by_id(&self, node_id: NodeId) -> Result<Option<Weight>, NodeDataError> {
+        match self.node_row(node_id)? {
+            Some(idx) => self.node_record_batch.weight(idx),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the position of the node with id `node_id`, or `None` if there
+    /// is no such node or it has no position.
+    pub fn node_position_by_id(&self, node_id: NodeId) -> Result<Option<Position>, NodeDataError> {
+        match self.node_row(node_id)? {
+            Some(idx) => self.node_record_batch.position(idx),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the label of the node with id `node_id`, or `None` if there is
+    /// no such node or it has no label.
+    pub fn node_label_by_id(&self, node_id: NodeId) -> Result<Option<&str>, NodeDataError> {
+        match self.node_row(node_id)? {
+            Some(idx) => self.node_record_batch.label(idx),
+            None => Ok(None),
+        }
+    }
+
+    fn node_key_index(&self) -> Result<&HashMap<String, NodeId>, NodeDataError> {
+        if let Some(index) = self.node_keys.get() {
+            return Ok(index);
+        }
+        if !self.node_record_batch.has_keys() {
+            return Ok(self.node_keys.get_or_init(HashMap::new));
+        }
+        let keys = self.node_record_batch.keys()?;
+        let node_ids = self.node_record_batch.node_ids()?;
+        let keys = keys.chunks().iter().flat_map(|keys| keys.iter());
+        let index = keys
+            .zip(node_ids.values())
+            .filter_map(|(key, node_id)| Some((key?.to_string(), node_id)))
+            .collect();
+        Ok(self.node_keys.get_or_init(|| index))
+    }
+
+    /// Returns the id of the node with the external `key`, or `None` if no
+    /// node has it.
+    pub fn node_id_for_key(&self, key: &str) -> Result<Option<NodeId>, NodeDataError> {
+        Ok(self.node_key_index()?.get(key).copied())
+    }
+
+    /// Returns the external key of the node with id `node_id`.
+    pub fn key_for_node_id(&self, node_id: NodeId) -> Result<Option<&str>, NodeDataError> {
+        if !self.node_record_batch.has_keys() {
+            return Ok(None);
+        }
+        match self.node_row(node_id)? {
+            Some(idx) => self.node_record_batch.key(idx),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the property column `name` of the node at `idx`.
+    pub fn node_property<T: PropertyValue>(
+        &self,
+        idx: usize,
+        name: &str,
+    ) -> Result<Option<T>, NodeDataError> {
+        self.node_record_batch.property(idx, name)
+    }
+
     pub fn num_edges(&self) -> usize {
         self.edge_record_batch.num_edges()
     }
 
+    pub fn edge_id(&self, idx: usize) -> Result<EdgeId, EdgeDataError> {
+        self.edge_record_batch.edge_id(idx)
+    }
+
+    pub fn edge(&self, idx: usize) -> Result<Edge, EdgeDataError> {
+        self.edge_record_batch.edge(idx)
+    }
+
+    /// Iterates over the edges without materializing them all.
+    pub fn edges_iter(&self) -> Result<impl Iterator<Item = Edge> + '_, EdgeDataError> {
+        self.edge_record_batch.edges_iter()
+    }
+
+    /// Source ids of every edge, borrowed from the Arrow buffer.
+    ///
+    /// Fails if the edges span more than one chunk, as they do after edits
+    /// until [`Self::compact`] is called.
+    pub fn sources_slice(&self) -> Result<&[NodeId], EdgeDataError> {
+        self.edge_record_batch.sources_slice()
+    }
+
+    /// Target ids of every edge, borrowed from the Arrow buffer.
+    ///
+    /// Fails if the edges span more than one chunk, as they do after edits
+    /// until [`Self::compact`] is called.
+    pub fn targets_slice(&self) -> Result<&[NodeId], EdgeDataError> {
+        self.edge_record_batch.targets_slice()
+    }
+
+    /// Reads the property column `name` of the edge at `idx`.
+    pub fn edge_property<T: PropertyValue>(
+        &self,
+        idx: usize,
+        name: &str,
+    ) -> Result<Option<T>, EdgeDataError> {
+        self.edge_record_batch.property(idx, name)
+    }
+
+    /// Returns the row of the edge with the given id, building the id index
+    /// on first use.
+    pub fn edge_row(&self, id: EdgeId) -> Result<Option<usize>, EdgeDataError> {
+        let edge_rows = match self.edge_rows.get() {
+            Some(edge_rows) => edge_rows,
+            None => {
+                let edge_ids = self.edge_record_batch.edge_ids()?;
+                let edge_rows = edge_ids.values().enumerate().map(|(idx, id)| (id, idx)).collect();
+                self.edge_rows.get_or_init(|| edge_rows)
+            }
+        };
+        Ok(edge_rows.get(&id).copied())
+    }
+
+    pub fn edge_by_id(&self, id: EdgeId) -> Result<Option<Edge>, EdgeDataError> {
+        self.edge_row(id)?.map(|idx| self.edge(idx)).transpose()
+    }
+
+    /// Returns the ids of all edges leading from `source` to `target`.
+    pub fn edges_between(
+        &self,
+        source: NodeId,
+        target: NodeId,
+    ) -> Result<Vec<EdgeId>, EdgeDataError> {
+        let index = self.adjacency()?;
+        index
+            .neighbors(source)
+            .iter()
+            .zip(index.edges(source))
+            .filter(|(neighbor, _)| **neighbor == target)
+            .map(|(_, idx)| self.edge_id(*idx))
+            .collect()
+    }
+
     pub fn source_id(&self, idx: usize) -> Result<NodeId, EdgeDataError> {
         self.edge_record_batch.source_id(idx)
     }
@@ -75,15 +413,75 @@ impl Graph {
         self.edge_record_batch.weight(idx)
     }
 
+    pub fn edge_type(&self, idx: usize) -> Result<Option<&str>, EdgeDataError> {
+        self.edge_record_batch.edge_type(idx)
+    }
+
+    /// Returns the outgoing adjacency index, building it on first use.
+    ///
+    /// Undirected graphs index every edge in both directions.
+    pub fn adjacency(&self) -> Result<&AdjacencyIndex, EdgeDataError> {
+        if let Some(index) = self.outgoing.get() {
+            return Ok(index);
+        }
+        let index = match self.kind {
+            GraphKind::Directed => self.edge_record_batch.adjacency_index()?,
+            GraphKind::Undirected => self.edge_record_batch.undirected_adjacency_index()?,
+        };
+        Ok(self.outgoing.get_or_init(|| index))
+    }
+
+    /// Returns the incoming adjacency index, building it on first use.
+    ///
+    /// Undirected graphs share the index with [`Self::adjacency`].
+    pub fn reverse_adjacency(&self) -> Result<&AdjacencyIndex, EdgeDataError> {
+        if self.kind == GraphKind::Undirected {
+            return self.adjacency();
+        }
+        if let Some(index) = self.incoming.get() {
+            return Ok(index);
+        }
+        let index = self.edge_record_batch.reverse_adjacency_index()?;
+        Ok(self.incoming.get_or_init(|| index))
+    }
+
     pub fn neighbors(&self, node: NodeId) -> Result<Vec<NodeId>, EdgeDataError> {
-        self.edge_record_batch.neighbors(node)
+        Ok(self.adjacency()?.neighbors(node).to_vec())
     }
 
     pub fn neighbors_with_weights(
         &self,
         node: NodeId,
     ) -> Result<Vec<(NodeId, f64)>, EdgeDataError> {
-        self.edge_record_batch.neighbors_with_weights(node)
+        let index = self.adjacency()?;
+        Ok(index
+            .neighbors(node)
+            .iter()
+            .zip(index.weights(node))
+            .filter_map(|(&node, &weight)| Some((node, weight?)))
+            .collect())
+    }
+
+    pub fn predecessors(&self, node: NodeId) -> Result<Vec<NodeId>, EdgeDataError> {
+        Ok(self.reverse_adjacency()?.neighbors(node).to_vec())
+    }
+
+    pub fn incoming_with_weights(&self, node: NodeId) -> Result<Vec<(NodeId, f64)>, EdgeDataError> {
+        let index = self.reverse_adjacency()?;
+        Ok(index
+            .neighbors(node)
+            .iter()
+            .zip(index.weights(node))
+            .filter_map(|(&node, &weight)| Some((node, weight?)))
+            .collect())
+    }
+
+    pub fn out_degree(&self, node: NodeId) -> Result<usize, EdgeDataError> {
+        Ok(self.adjacency()?.degree(node))
+    }
+
+    pub fn in_degree(&self, node: NodeId) -> Result<usize, EdgeDataError> {
+        Ok(self.reverse_adjacency()?.degree(node))
     }
 }
 
@@ -94,39 +492,133 @@ pub struct GraphData {
     nodes: Vec<Node>,
     #[allow(unused)]
     edges: Vec<Edge>,
+    // Edges between string keys, interned into node ids on build
+    #[allow(unused)]
+    keyed_edges: Vec<KeyedEdge>,
+    #[allow(unused)]
+    kind: GraphKind,
+    // How edges without a weight are treated by the algorithms
+    #[allow(unused)]
+    missing_weight: MissingWeight,
+    // Fails the build if validation finds problems
+    #[allow(unused)]
+    strict: bool,
 }
 
 impl GraphBuilder {
     pub fn build(&self) -> Result<Graph, GraphError> {
-        let edges =
-            self.edges.as_ref().ok_or(GraphError::EmptyGraph { name: "no edges".to_string() })?;
+        if self.edges.is_none() && self.keyed_edges.is_none() {
+            return Err(GraphError::EmptyGraph { name: "no edges".to_string() });
+        }
+        let edges = self.edges.as_deref().unwrap_or_default();
+        let keyed_edges = self.keyed_edges.as_deref().unwrap_or_default();
+        let mut columns = EdgeColumns::with_capacity(edges.len() + keyed_edges.len());
+        columns.extend(edges.iter().cloned());
 
-        let nodes = match self.nodes.as_ref() {
-            Some(nodes) => nodes.clone(),
-            None => {
-                let mut nodes = Vec::new();
-                for edge in edges.clone() {
-                    if !nodes.iter().any(|node: &Node| node.id == edge.source_id) {
-                        nodes.push(Node { id: edge.source_id, weight: None, position: None });
-                    }
-                    if !nodes.iter().any(|node: &Node| node.id == edge.target_id) {
-                        nodes.push(Node { id: edge.target_id, weight: None, position: None });
-                    }
+        // Keys get fresh ids after any id already in use, unless a given node has the key.
+        let mut keyed_nodes = Vec::new();
+        if !keyed_edges.is_empty() {
+            let given_nodes = self.nodes.iter().flatten();
+            let mut node_ids = given_nodes
+                .clone()
+                .filter_map(|node| Some((node.key.clone()?, node.id)))
+                .collect::<HashMap<_, _>>();
+            // `None` once every id is taken
+            let mut next_id = match given_nodes
+                .map(|node| node.id)
+                .chain(columns.sources().iter().copied())
+                .chain(columns.targets().iter().copied())
+                .max()
+            {
+                Some(id) => id.checked_add(1),
+                None => Some(0),
+            };
+            let mut intern = |key: &String| {
+                if let Some(&id) = node_ids.get(key) {
+                    return Ok(id);
                 }
-                nodes
+                let id = next_id.ok_or(GraphError::NodeIdOverflow)?;
+                next_id = id.checked_add(1);
+                node_ids.insert(key.clone(), id);
+                keyed_nodes.push(Node {
+                    id,
+                    key: Some(key.clone()),
+                    weight: None,
+                    position: None,
+                    label: None,
+                });
+                Ok(id)
+            };
+            for keyed_edge in keyed_edges {
+                let source_id = intern(&keyed_edge.source)?;
+                let target_id = intern(&keyed_edge.target)?;
+                columns.push(Edge {
+                    id: None,
+                    source_id,
+                    target_id,
+                    weight: keyed_edge.weight,
+                    timestamp: None,
+                    valid_from: None,
+                    valid_to: None,
+                    edge_type: keyed_edge.edge_type.clone(),
+                });
+            }
+        }
+
+        let node_record_batch = match self.nodes.as_ref() {
+            Some(nodes) => {
+                NodeRecordBatch::from(nodes.iter().cloned().chain(keyed_nodes).collect::<Vec<_>>())
+            }
+            None if keyed_nodes.is_empty() => {
+                NodeRecordBatch::from_node_ids(infer_node_ids(columns.sources(), columns.targets()))
+            }
+            None => {
+                let mut node_ids = infer_node_ids(columns.sources(), columns.targets());
+                let keyed = keyed_nodes.iter().map(|node| node.id).collect::<HashSet<_>>();
+                node_ids.retain(|node_id| !keyed.contains(node_id));
+                let inferred = node_ids.into_iter().map(|id| Node {
+                    id,
+                    key: None,
+                    weight: None,
+                    position: None,
+                    label: None,
+                });
+                NodeRecordBatch::from(keyed_nodes.into_iter().chain(inferred).collect::<Vec<_>>())
             }
         };
-        let edge_record_batch = EdgeRecordBatch::from(edges.clone());
-        let node_record_batch = NodeRecordBatch::from(nodes);
+        let mut edge_record_batch = EdgeRecordBatch::try_from(columns)
+            .context(EdgeDataSnafu {})?
+            .with_kind(self.kind.unwrap_or_default());
+        if let Some(policy) = self.missing_weight {
+            edge_record_batch = edge_record_batch.with_missing_weight(policy);
+        }
 
-        let graph = Graph::from_arrow_record_batches(node_record_batch, edge_record_batch)?;
+        let graph = match self.strict.unwrap_or_default() {
+            true => Graph::from_arrow_record_batches_strict(node_record_batch, edge_record_batch)?,
+            false => Graph::from_arrow_record_batches(node_record_batch, edge_record_batch)?,
+        };
         Ok(graph)
     }
 }
 
+/// Ids of the ends of the edges, in order of first appearance.
+fn infer_node_ids(sources: &[NodeId], targets: &[NodeId]) -> Vec<NodeId> {
+    let mut seen = HashSet::new();
+    sources
+        .iter()
+        .zip(targets)
+        .flat_map(|(&source_id, &target_id)| [source_id, target_id])
+        .filter(|&node_id| seen.insert(node_id))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use arrow::{
+        array::{Array, FixedSizeListArray, Float64Array, Int64Array, StringArray},
+        datatypes::{DataType, Field, Schema},
+    };
 
     #[test]
     fn test_graph_with_weight() {
@@ -175,4 +667,399 @@ mod tests {
             assert_eq!(graph.target_id(idx).unwrap(), (idx + 2) as NodeId);
         }
     }
+
+    #[test]
+    fn test_graph_neighbors() {
+        let edges = vec![
+            Edge::builder().source_id(1).target_id(2).weight(1.0).build().unwrap(),
+            Edge::builder().source_id(3).target_id(1).weight(2.0).build().unwrap(),
+            Edge::builder().source_id(1).target_id(3).weight(3.0).build().unwrap(),
+            Edge::builder().source_id(2).target_id(3).weight(4.0).build().unwrap(),
+        ];
+        let graph = Graph::builder().edges(edges).build().unwrap();
+        assert_eq!(graph.neighbors(1).unwrap(), vec![2, 3]);
+        assert_eq!(graph.neighbors_with_weights(1).unwrap(), vec![(2, 1.0), (3, 3.0)]);
+        assert_eq!(graph.neighbors(3).unwrap(), vec![1]);
+        assert!(graph.neighbors(4).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_graph_incoming() {
+        let edges = vec![
+            Edge::builder().source_id(1).target_id(3).weight(1.0).build().unwrap(),
+            Edge::builder().source_id(2).target_id(3).weight(2.0).build().unwrap(),
+            Edge::builder().source_id(3).target_id(4).weight(3.0).build().unwrap(),
+        ];
+        let graph = Graph::builder().edges(edges).build().unwrap();
+        assert_eq!(graph.predecessors(3).unwrap(), vec![1, 2]);
+        assert_eq!(graph.incoming_with_weights(3).unwrap(), vec![(1, 1.0), (2, 2.0)]);
+        assert!(graph.predecessors(1).unwrap().is_empty());
+        assert_eq!(graph.in_degree(3).unwrap(), 2);
+        assert_eq!(graph.out_degree(3).unwrap(), 1);
+        assert_eq!(graph.in_degree(1).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_undirected_graph() {
+        let edges = vec![
+            Edge::builder().source_id(1).target_id(2).weight(1.0).build().unwrap(),
+            Edge::builder().source_id(2).target_id(3).weight(2.0).build().unwrap(),
+            Edge::builder().source_id(3).target_id(3).weight(3.0).build().unwrap(),
+        ];
+        let graph = Graph::builder().edges(edges).kind(GraphKind::Undirected).build().unwrap();
+        assert_eq!(graph.kind(), GraphKind::Undirected);
+        assert_eq!(graph.neighbors(2).unwrap(), vec![1, 3]);
+        assert_eq!(graph.neighbors_with_weights(3).unwrap(), vec![(2, 2.0), (3, 3.0)]);
+        assert_eq!(graph.predecessors(1).unwrap(), vec![2]);
+        assert_eq!(graph.in_degree(2).unwrap(), graph.out_degree(2).unwrap());
+
+        let graph = Graph::from_arrow_record_batches(
+            NodeRecordBatch::from(graph.node_record_batch().as_ref().clone()),
+            EdgeRecordBatch::from(graph.edge_record_batch().as_ref().clone()),
+        )
+        .unwrap();
+        assert_eq!(graph.kind(), GraphKind::Undirected);
+    }
+
+    #[test]
+    fn test_parallel_edges() {
+        let edges = vec![
+            Edge::builder().source_id(1).target_id(2).weight(1.0).build().unwrap(),
+            Edge::builder().id(10u64).source_id(1).target_id(2).weight(2.0).build().unwrap(),
+            Edge::builder().source_id(2).target_id(3).weight(3.0).build().unwrap(),
+        ];
+        let graph = Graph::builder().edges(edges).build().unwrap();
+        assert_eq!(graph.edge_id(0).unwrap(), 11);
+        assert_eq!(graph.edge_id(2).unwrap(), 12);
+        assert_eq!(graph.edges_between(1, 2).unwrap(), vec![11, 10]);
+        assert_eq!(graph.edges_between(2, 1).unwrap(), Vec::<EdgeId>::new());
+
+        let edge = graph.edge_by_id(10).unwrap().unwrap();
+        assert_eq!((edge.source_id, edge.target_id, edge.weight), (1, 2, Some(2.0)));
+        assert_eq!(graph.edge_by_id(3).unwrap(), None);
+    }
+
+    #[test]
+    fn test_property_columns() {
+        let node_record_batch = NodeRecordBatch::from(vec![
+            Node::builder().id(1).build().unwrap(),
+            Node::builder().id(2).build().unwrap(),
+        ]);
+        let node_record_batch = node_record_batch.to_record_batch();
+        let mut fields = node_record_batch.schema().fields().to_vec();
+        fields.push(Arc::new(Field::new("label", DataType::Utf8, true)));
+        let mut columns = node_record_batch.columns().to_vec();
+        columns.push(Arc::new(StringArray::from(vec![Some("user"), None])));
+        let node_record_batch =
+            RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap();
+
+        let mut edge_record_batch =
+            EdgeRecordBatch::from(vec![Edge::builder().source_id(1).target_id(2).build().unwrap()]);
+        let record_batch = edge_record_batch.to_record_batch();
+        let mut fields = record_batch.schema().fields().to_vec();
+        fields.push(Arc::new(Field::new("since", DataType::Int64, true)));
+        let mut columns = record_batch.columns().to_vec();
+        columns.push(Arc::new(Int64Array::from(vec![1_700_000_000])));
+        edge_record_batch = EdgeRecordBatch::from(
+            RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap(),
+        );
+        edge_record_batch
+            .add_edges(vec![Edge::builder().source_id(2).target_id(1).build().unwrap()])
+            .unwrap();
+        assert_eq!(edge_record_batch.property_names(), vec!["since"]);
+
+        let graph = Graph::from_arrow_record_batches(
+            NodeRecordBatch::from(node_record_batch),
+            edge_record_batch,
+        )
+        .unwrap();
+        assert_eq!(graph.node_property::<String>(0, "label").unwrap(), Some("user".to_string()));
+        assert_eq!(graph.node_property::<String>(1, "label").unwrap(), None);
+        assert!(graph.node_property::<i64>(0, "label").is_err());
+        assert!(graph.node_property::<String>(0, "missing").is_err());
+        assert_eq!(graph.edge_property::<i64>(0, "since").unwrap(), Some(1_700_000_000));
+        assert_eq!(graph.edge_property::<i64>(1, "since").unwrap(), None);
+    }
+
+    #[test]
+    fn test_missing_required_column() {
+        let edge_record_batch =
+            EdgeRecordBatch::from(vec![Edge::builder().source_id(1).target_id(2).build().unwrap()]);
+        let edge_record_batch =
+            EdgeRecordBatch::from(edge_record_batch.to_record_batch().project(&[0, 1, 3]).unwrap());
+        let node_record_batch = NodeRecordBatch::from(vec![Node::builder().id(1).build().unwrap()]);
+        assert!(matches!(
+            Graph::from_arrow_record_batches(node_record_batch, edge_record_batch),
+            Err(GraphError::InvalidEdgeSchema)
+        ));
+    }
+
+    #[test]
+    fn test_keyed_edges() {
+        let keyed_edges = vec![
+            KeyedEdge::builder().source("alice").target("bob").weight(1.0).build().unwrap(),
+            KeyedEdge::builder().source("bob").target("carol").build().unwrap(),
+            KeyedEdge::builder().source("alice").target("carol").build().unwrap(),
+        ];
+        let graph = Graph::builder().keyed_edges(keyed_edges).build().unwrap();
+        assert_eq!(graph.num_nodes(), 3);
+        assert_eq!(graph.num_edges(), 3);
+
+        let alice = graph.node_id_for_key("alice").unwrap().unwrap();
+        let bob = graph.node_id_for_key("bob").unwrap().unwrap();
+        let carol = graph.node_id_for_key("carol").unwrap().unwrap();
+        assert_eq!(graph.neighbors(alice).unwrap(), vec![bob, carol]);
+        assert_eq!(graph.key_for_node_id(carol).unwrap(), Some("carol"));
+        assert_eq!(graph.node_id_for_key("dave").unwrap(), None);
+        assert_eq!(graph.key_for_node_id(42).unwrap(), None);
+    }
+
+    #[test]
+    fn test_keyed_edges_with_nodes() {
+        let nodes = vec![
+            Node::builder().id(7).key("alice").build().unwrap(),
+            Node::builder().id(9).build().unwrap(),
+        ];
+        let keyed_edges = vec![KeyedEdge::builder().source("alice").target("bob").build().unwrap()];
+        let graph = Graph::builder().nodes(nodes).keyed_edges(keyed_edges).build().unwrap();
+        assert_eq!(graph.num_nodes(), 3);
+        assert_eq!(graph.node_id_for_key("alice").unwrap(), Some(7));
+        assert_eq!(graph.node_id_for_key("bob").unwrap(), Some(10));
+        assert_eq!(graph.neighbors(7).unwrap(), vec![10]);
+        assert_eq!(graph.key_for_node_id(9).unwrap(), None);
+
+        let graph = Graph::builder()
+            .edges(vec![Edge::builder().source_id(1).target_id(2).build().unwrap()])
+            .build()
+            .unwrap();
+        assert_eq!(graph.node_id_for_key("alice").unwrap(), None);
+        assert_eq!(graph.key_for_node_id(1).unwrap(), None);
+
+        let nodes = vec![
+            Node::builder().id(1).key("a").build().unwrap(),
+            Node::builder().id(2).key("a").build().unwrap(),
+        ];
+        let edges = vec![Edge::builder().source_id(1).target_id(2).build().unwrap()];
+        assert!(matches!(
+            Graph::builder().nodes(nodes).edges(edges).build(),
+            Err(GraphError::NodeData { source: NodeDataError::DuplicateKey { .. } })
+        ));
+    }
+
+    #[test]
+    fn test_id_overflow() {
+        let last = Edge::builder().id(EdgeId::MAX).source_id(1).target_id(2).build().unwrap();
+        let keyed_edges = vec![KeyedEdge::builder().source("a").target("b").build().unwrap()];
+        let graph = Graph::builder().edges(vec![last.clone()]).build().unwrap();
+        assert_eq!(graph.edge_id(0).unwrap(), EdgeId::MAX);
+        assert!(matches!(
+            Graph::builder().edges(vec![last]).keyed_edges(keyed_edges.clone()).build(),
+            Err(GraphError::EdgeData { source: EdgeDataError::EdgeIdOverflow })
+        ));
+        assert!(matches!(
+            graph.edge_data().clone().add_edges(vec![Edge::builder()
+                .source_id(2)
+                .target_id(1)
+                .build()
+                .unwrap()]),
+            Err(EdgeDataError::EdgeIdOverflow)
+        ));
+
+        let nodes = vec![Node::builder().id(NodeId::MAX).build().unwrap()];
+        assert!(matches!(
+            Graph::builder().nodes(nodes).keyed_edges(keyed_edges).build(),
+            Err(GraphError::NodeIdOverflow)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_position_size() {
+        let nodes = vec![Node::builder().id(1).position((0.0, 1.0)).build().unwrap()];
+        let record_batch = NodeRecordBatch::from(nodes).to_record_batch();
+        let idx = record_batch.schema().index_of("position").unwrap();
+        let item = Arc::new(Field::new("item", DataType::Float64, false));
+        let positions =
+            FixedSizeListArray::new(item, 1, Arc::new(Float64Array::from(vec![0.0])), None);
+        let mut fields = record_batch.schema().fields().to_vec();
+        fields[idx] = Arc::new(Field::new("position", positions.data_type().clone(), true));
+        let mut columns = record_batch.columns().to_vec();
+        columns[idx] = Arc::new(positions);
+        let node_record_batch = NodeRecordBatch::from(
+            RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap(),
+        );
+
+        assert!(node_record_batch.dimension().is_err());
+        assert!(node_record_batch.position(0).is_err());
+        assert!(node_record_batch.nodes_iter().is_err());
+    }
+
+    #[test]
+    fn test_node_positions() {
+        let nodes = vec![
+            Node::builder().id(1).position((0.0, 0.0)).build().unwrap(),
+            Node::builder().id(2).position((3.0, 4.0)).build().unwrap(),
+            Node::builder().id(3).build().unwrap(),
+        ];
+        let edges = vec![Edge::builder().source_id(1).target_id(2).build().unwrap()];
+        let graph = Graph::builder().nodes(nodes).edges(edges.clone()).build().unwrap();
+        assert_eq!(graph.node_position(1).unwrap(), Some(Position::new_2d(3.0, 4.0)));
+        assert_eq!(graph.node_position(2).unwrap(), None);
+        assert_eq!(
+            graph.node_position(0).unwrap().unwrap().distance(&Position::new_2d(3.0, 4.0)),
+            5.0
+        );
+
+        let nodes = vec![
+            Node::builder().id(1).position((1.0, 2.0, 3.0)).build().unwrap(),
+            Node::builder().id(2).position((3.0, 4.0)).build().unwrap(),
+        ];
+        let graph = Graph::builder().nodes(nodes).edges(edges).build().unwrap();
+        assert_eq!(graph.node_position(0).unwrap(), Some(Position::new_3d(1.0, 2.0, 3.0)));
+        assert_eq!(graph.node_position(1).unwrap(), Some(Position::new_3d(3.0, 4.0, 0.0)));
+        assert_eq!(graph.node(1).unwrap().position, Some(Position::new_3d(3.0, 4.0, 0.0)));
+    }
+
+    #[test]
+    fn test_iterators_and_slices() {
+        let nodes = vec![
+            Node::builder().id(1).key("alice").position((1.0, 2.0)).build().unwrap(),
+            Node::builder().id(2).build().unwrap(),
+            Node::builder().id(3).weight(0.5).build().unwrap(),
+        ];
+        let edges = vec![
+            Edge::builder().source_id(1).target_id(2).weight(1.0).build().unwrap(),
+            Edge::builder().source_id(2).target_id(3).weight(2.0).build().unwrap(),
+        ];
+        let mut graph = Graph::builder().nodes(nodes).edges(edges).build().unwrap();
+        assert_eq!(
+            graph.nodes_iter().unwrap().collect::<Vec<_>>(),
+            (0..3).map(|idx| graph.node(idx).unwrap()).collect::<Vec<_>>()
+        );
+        assert_eq!(graph.nodes_iter().unwrap().nth(1).unwrap().weight, None);
+        assert_eq!(graph.sources_slice().unwrap(), &[1, 2]);
+        assert_eq!(graph.targets_slice().unwrap(), &[2, 3]);
+
+        let mut edit = graph.edit();
+        edit.add_node(Node::builder().id(4).build().unwrap())
+            .add_edge(Edge::builder().source_id(3).target_id(1).build().unwrap());
+        edit.commit().unwrap();
+        assert!(graph.sources_slice().is_err());
+        assert_eq!(
+            graph.nodes_iter().unwrap().map(|node| node.id).collect::<Vec<_>>(),
+            [1, 2, 3, 4]
+        );
+        assert_eq!(
+            graph.edges_iter().unwrap().collect::<Vec<_>>(),
+            (0..3).map(|idx| graph.edge(idx).unwrap()).collect::<Vec<_>>()
+        );
+
+        graph.compact();
+        assert_eq!(graph.sources_slice().unwrap(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_node_lookup_by_id() {
+        let nodes = vec![
+            Node::builder().id(10).weight(2.5).build().unwrap(),
+            Node::builder().id(20).position((1.0, 1.0)).build().unwrap(),
+        ];
+        let edges = vec![Edge::builder().source_id(10).target_id(20).build().unwrap()];
+        let graph = Graph::builder().nodes(nodes).edges(edges).build().unwrap();
+        assert_eq!(graph.node_row(20).unwrap(), Some(1));
+        assert!(graph.contains_node(10).unwrap());
+        assert!(!graph.contains_node(30).unwrap());
+        assert_eq!(graph.node_by_id(20).unwrap().unwrap().id, 20);
+        assert_eq!(graph.node_by_id(30).unwrap(), None);
+        assert_eq!(graph.node_weight_by_id(10).unwrap(), Some(2.5));
+        assert_eq!(graph.node_weight_by_id(30).unwrap(), None);
+        assert_eq!(graph.node_position_by_id(20).unwrap(), Some(Position::new_2d(1.0, 1.0)));
+    }
+
+    #[test]
+    fn test_from_edges_and_columns() {
+        let edges = (0..4).map(|idx| {
+            Edge::builder()
+                .source_id(idx % 3)
+                .target_id(idx + 1)
+                .weight(idx as f64)
+                .build()
+                .unwrap()
+        });
+        let graph = Graph::from_edges(edges, GraphKind::Undirected).unwrap();
+        assert_eq!(graph.kind(), GraphKind::Undirected);
+        assert_eq!(
+            graph.nodes_iter().unwrap().map(|node| node.id).collect::<Vec<_>>(),
+            [0, 1, 2, 3, 4]
+        );
+        assert_eq!(graph.edge_id(3).unwrap(), 3);
+        assert_eq!(graph.neighbors(0).unwrap(), vec![1, 4]);
+
+        let graph =
+            Graph::from_edge_columns(vec![5, 6, 5], vec![6, 7, 7], None, GraphKind::Directed)
+                .unwrap();
+        assert_eq!(graph.num_nodes(), 3);
+        assert_eq!(graph.sources_slice().unwrap(), &[5, 6, 5]);
+        assert_eq!(graph.weight(2).unwrap(), None);
+        assert_eq!(graph.neighbors(5).unwrap(), vec![6, 7]);
+        assert_eq!(graph.neighbors_with_weights(5).unwrap(), vec![(6, 0.0), (7, 0.0)]);
+        assert!(matches!(
+            Graph::from_edge_columns(vec![1, 2], vec![2], None, GraphKind::Directed),
+            Err(GraphError::EdgeData { .. })
+        ));
+    }
+
+    #[test]
+    fn test_missing_weights() {
+        let edges = vec![
+            Edge::builder().source_id(1).target_id(2).weight(1.0).build().unwrap(),
+            Edge::builder().source_id(2).target_id(3).build().unwrap(),
+            Edge::builder().source_id(1).target_id(3).weight(5.0).build().unwrap(),
+        ];
+        let mut graph = Graph::builder().edges(edges.clone()).build().unwrap();
+        assert_eq!(graph.weight(1).unwrap(), None);
+        assert_eq!(
+            graph.edges_iter().unwrap().map(|edge| edge.weight).collect::<Vec<_>>(),
+            [Some(1.0), None, Some(5.0)]
+        );
+        assert_eq!(graph.node_weight_by_id(1).unwrap(), None);
+        // Missing weights default to zero
+        assert_eq!(crate::dijkstra_search(&graph, 1, 3), Some(vec![1, 2, 3]));
+
+        let mut edit = graph.edit();
+        edit.add_edge(Edge::builder().source_id(3).target_id(1).build().unwrap());
+        edit.commit().unwrap();
+        assert_eq!(graph.weight(3).unwrap(), None);
+
+        let skipping = graph.with_missing_weight(MissingWeight::Skip).unwrap();
+        assert_eq!(skipping.missing_weight().unwrap(), MissingWeight::Skip);
+        assert_eq!(crate::dijkstra_search(&skipping, 1, 3), Some(vec![1, 3]));
+        // Skipped edges are still part of the topology.
+        assert_eq!(skipping.neighbors(3).unwrap(), vec![1]);
+        assert!(skipping.neighbors_with_weights(3).unwrap().is_empty());
+        assert_eq!(crate::breath_first_search(&skipping, 3, 1), Some(vec![3, 1]));
+        assert_eq!(crate::dijkstra_search(&skipping, 3, 1), None);
+        assert_eq!(
+            skipping.to_adjacency_matrix().unwrap(),
+            [0.0, 1.0, 5.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]
+        );
+        let (condensation, _) = skipping.condensation().unwrap();
+        assert_eq!(condensation.num_nodes(), 1);
+        let merged = skipping.merge_nodes(&[1, 2], crate::WeightMerge::Sum).unwrap();
+        assert_eq!(merged.neighbors_with_weights(1).unwrap(), vec![(3, 5.0)]);
+        assert_eq!(merged.neighbors(3).unwrap(), vec![1]);
+
+        assert!(matches!(
+            graph.with_missing_weight(MissingWeight::Error),
+            Err(GraphError::EdgeData { source: EdgeDataError::MissingWeight { edge_id: 1 } })
+        ));
+        let with_default = Graph::builder()
+            .edges(edges)
+            .missing_weight(MissingWeight::Default(10.0))
+            .build()
+            .unwrap();
+        assert_eq!(with_default.neighbors_with_weights(2).unwrap(), vec![(3, 10.0)]);
+        assert_eq!(with_default.to_adjacency_matrix().unwrap()[5], 10.0);
+        let merged = with_default.merge_nodes(&[2, 1], crate::WeightMerge::Sum).unwrap();
+        assert_eq!(merged.neighbors_with_weights(2).unwrap(), vec![(3, 15.0)]);
+    }
 }
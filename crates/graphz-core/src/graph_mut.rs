@@ -0,0 +1,305 @@
+use std::collections::{HashMap, HashSet};
+
+use arrow::array::BooleanArray;
+use snafu::prelude::*;
+
+use crate::{
+    edge::Edge,
+    graph::{EdgeDataSnafu, FailedToEditGraphSnafu, NodeDataSnafu},
+    node::Node,
+    types::{EdgeId, NodeId, Weight},
+    Graph, GraphError,
+};
+
+/// Batched edits to a [`Graph`].
+///
+/// Edits are collected and applied together by [`GraphMut::commit`], so the
+/// Arrow columns are filtered once per commit rather than once per call.
+/// Removals apply to the graph as it was before the edit, and additions are
+/// appended afterwards as new chunks. Added edges must join nodes of the
+/// edited graph, and weights are set on the edges of the edited graph, so on
+/// added edges but not on removed ones.
+#[derive(Debug)]
+pub struct GraphMut<'a> {
+    graph: &'a mut Graph,
+    added_nodes: Vec<Node>,
+    removed_nodes: HashSet<NodeId>,
+    added_edges: Vec<Edge>,
+    removed_edges: HashSet<EdgeId>,
+    weights: HashMap<EdgeId, Weight>,
+}
+
+impl Graph {
+    pub fn edit(&mut self) -> GraphMut<'_> {
+        GraphMut {
+            graph: self,
+            added_nodes: Vec::new(),
+            removed_nodes: HashSet::new(),
+            added_edges: Vec::new(),
+            removed_edges: HashSet::new(),
+            weights: HashMap::new(),
+        }
+    }
+}
+
+impl GraphMut<'_> {
+    pub fn add_node(&mut self, node: Node) -> &mut Self {
+        self.added_nodes.push(node);
+        self
+    }
+
+    /// Removes the node and every existing edge from or to it.
+    pub fn remove_node(&mut self, node_id: NodeId) -> &mut Self {
+        self.removed_nodes.insert(node_id);
+        self
+    }
+
+    pub fn add_edge(&mut self, edge: Edge) -> &mut Self {
+        self.added_edges.push(edge);
+        self
+    }
+
+    /// Adds edges; ids are assigned on commit to edges without one.
+    pub fn add_edges(&mut self, edges: impl IntoIterator<Item = Edge>) -> &mut Self {
+        self.added_edges.extend(edges);
+        self
+    }
+
+    pub fn remove_edge(&mut self, edge_id: EdgeId) -> &mut Self {
+        self.removed_edges.insert(edge_id);
+        self
+    }
+
+    pub fn set_weight(&mut self, edge_id: EdgeId, weight: Weight) -> &mut Self {
+        self.weights.insert(edge_id, weight);
+        self
+    }
+
+    /// Applies the edits, rebuilding the record batches of the graph once.
+    pub fn commit(self) -> Result<(), GraphError> {
+        let graph = self.graph;
+        // Cloning shares the chunks, and additions are appended as new chunks.
+        let mut node_record_batch = graph.node_data().clone();
+        let mut edge_record_batch = graph.edge_data().clone();
+
+        for &edge_id in &self.removed_edges {
+            if graph.edge_row(edge_id).context(EdgeDataSnafu {})?.is_none() {
+                return Err(GraphError::EdgeNotFound { edge_id });
+            }
+        }
+
+        for &node_id in self.removed_nodes.iter() {
+            if !graph.contains_node(node_id).context(NodeDataSnafu {})? {
+                return Err(GraphError::NodeNotFound { node_id });
+            }
+        }
+
+        if !self.removed_nodes.is_empty() {
+            let node_ids = node_record_batch.node_ids().context(NodeDataSnafu {})?;
+            let keep = node_ids
+                .values()
+                .map(|node_id| !self.removed_nodes.contains(&node_id))
+                .collect::<Vec<_>>();
+            let keep = BooleanArray::from(keep);
+            node_record_batch =
+                node_record_batch.filter(&keep).context(FailedToEditGraphSnafu {})?;
+        }
+
+        // Ids are taken before removals so removed edge ids are never reused.
+        let next_edge_id = edge_record_batch.next_edge_id().context(EdgeDataSnafu {})?;
+        if !self.removed_nodes.is_empty() || !self.removed_edges.is_empty() {
+            let edge_ids = edge_record_batch.edge_ids().context(EdgeDataSnafu {})?;
+            let source_ids = edge_record_batch.source_ids().context(EdgeDataSnafu {})?;
+            let target_ids = edge_record_batch.target_ids().context(EdgeDataSnafu {})?;
+            let keep = edge_ids
+                .values()
+                .zip(source_ids.values())
+                .zip(target_ids.values())
+                .map(|((edge_id, source_id), target_id)| {
+                    !self.removed_edges.contains(&edge_id)
+                        && !self.removed_nodes.contains(&source_id)
+                        && !self.removed_nodes.contains(&target_id)
+                })
+                .collect::<Vec<_>>();
+            let keep = BooleanArray::from(keep);
+            edge_record_batch = edge_record_batch
+                .filter(&keep)
+                .context(FailedToEditGraphSnafu {})?
+                .with_next_edge_id(next_edge_id);
+        }
+
+        if !self.added_nodes.is_empty() || !self.added_edges.is_empty() {
+            let node_ids = node_record_batch.node_ids().context(NodeDataSnafu {})?;
+            let mut node_ids = node_ids.values().collect::<HashSet<_>>();
+            if let Some(node) = self.added_nodes.iter().find(|node| !node_ids.insert(node.id)) {
+                return Err(GraphError::DuplicateNodeId { node_id: node.id });
+            }
+            let mut ends =
+                self.added_edges.iter().flat_map(|edge| [edge.source_id, edge.target_id]);
+            if let Some(node_id) = ends.find(|node_id| !node_ids.contains(node_id)) {
+                return Err(GraphError::NodeNotFound { node_id });
+            }
+        }
+
+        if !self.added_nodes.is_empty() {
+            node_record_batch.add_nodes(self.added_nodes).context(NodeDataSnafu {})?;
+        }
+        if !self.added_edges.is_empty() {
+            edge_record_batch
+                .add_edges_from_id(&self.added_edges, next_edge_id)
+                .context(EdgeDataSnafu {})?;
+        }
+        if !self.weights.is_empty() {
+            let edge_ids = edge_record_batch.edge_ids().context(EdgeDataSnafu {})?;
+            let edge_ids = edge_ids.values().collect::<HashSet<_>>();
+            if let Some(&edge_id) = self.weights.keys().find(|edge_id| !edge_ids.contains(edge_id))
+            {
+                return Err(GraphError::EdgeNotFound { edge_id });
+            }
+            edge_record_batch.set_weights(&self.weights).context(EdgeDataSnafu {})?;
+        }
+
+        // Rebuilding the graph also drops the indexes of the old batches.
+        *graph = Graph::from_arrow_record_batches(node_record_batch, edge_record_batch)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn build_graph() -> Graph {
+        let edges = vec![
+            Edge::builder().source_id(1).target_id(2).weight(1.0).build().unwrap(),
+            Edge::builder().source_id(2).target_id(3).weight(2.0).build().unwrap(),
+            Edge::builder().source_id(3).target_id(1).weight(3.0).build().unwrap(),
+        ];
+        Graph::builder().edges(edges).build().unwrap()
+    }
+
+    #[test]
+    fn test_add_and_remove() {
+        let mut graph = build_graph();
+        assert_eq!(graph.neighbors(1).unwrap(), vec![2]);
+
+        let mut edit = graph.edit();
+        edit.add_node(Node::builder().id(4).build().unwrap())
+            .add_edges(vec![Edge::builder().source_id(1).target_id(4).build().unwrap()])
+            .remove_edge(0)
+            .set_weight(1, 5.0);
+        edit.commit().unwrap();
+
+        assert_eq!(graph.num_nodes(), 4);
+        assert_eq!(graph.num_edges(), 3);
+        assert_eq!(graph.neighbors(1).unwrap(), vec![4]);
+        assert_eq!(graph.edge_by_id(0).unwrap(), None);
+        assert_eq!(graph.edge_by_id(1).unwrap().unwrap().weight, Some(5.0));
+        assert_eq!(graph.edges_between(1, 4).unwrap(), vec![3]);
+
+        // The chunks are concatenated once.
+        let edge_record_batch = graph.edge_record_batch();
+        assert_eq!(edge_record_batch.num_rows(), 3);
+        assert!(Arc::ptr_eq(&edge_record_batch, &graph.edge_record_batch()));
+    }
+
+    #[test]
+    fn test_remove_node_cascades() {
+        let mut graph = build_graph();
+        let mut edit = graph.edit();
+        edit.remove_node(3);
+        edit.commit().unwrap();
+
+        assert_eq!(graph.num_nodes(), 2);
+        assert_eq!(graph.num_edges(), 1);
+        assert!(graph.predecessors(1).unwrap().is_empty());
+        assert_eq!(graph.neighbors(1).unwrap(), vec![2]);
+
+        // Ids of removed edges are not reused.
+        let mut edit = graph.edit();
+        edit.add_edge(Edge::builder().source_id(2).target_id(1).build().unwrap());
+        edit.commit().unwrap();
+        assert_eq!(graph.edges_between(2, 1).unwrap(), vec![3]);
+    }
+
+    #[test]
+    fn test_add_node_to_positioned_graph() {
+        let nodes = vec![
+            Node::builder().id(1).position((0.0, 1.0)).build().unwrap(),
+            Node::builder().id(2).position((1.0, 0.0)).build().unwrap(),
+        ];
+        let edges = vec![Edge::builder().source_id(1).target_id(2).build().unwrap()];
+        let mut graph = Graph::builder().nodes(nodes).edges(edges).build().unwrap();
+        let mut edit = graph.edit();
+        edit.add_node(Node::builder().id(3).build().unwrap());
+        edit.commit().unwrap();
+        assert_eq!(graph.num_nodes(), 3);
+        assert_eq!(graph.node(2).unwrap().position, None);
+
+        let mut edit = graph.edit();
+        edit.add_node(Node::builder().id(4).position((0.0, 0.0, 1.0)).build().unwrap());
+        assert!(edit.commit().is_err());
+    }
+
+    #[test]
+    fn test_unknown_ids() {
+        let mut graph = build_graph();
+        let mut edit = graph.edit();
+        edit.remove_edge(7);
+        assert!(matches!(edit.commit(), Err(GraphError::EdgeNotFound { edge_id: 7 })));
+
+        let mut edit = graph.edit();
+        edit.remove_node(7);
+        assert!(matches!(edit.commit(), Err(GraphError::NodeNotFound { node_id: 7 })));
+        assert_eq!(graph.num_edges(), 3);
+    }
+
+    #[test]
+    fn test_invalid_edits() {
+        let mut graph = build_graph();
+        let mut edit = graph.edit();
+        edit.add_edge(Edge::builder().source_id(7).target_id(8).build().unwrap());
+        assert!(matches!(edit.commit(), Err(GraphError::NodeNotFound { node_id: 7 })));
+
+        // Edges added in the same edit are not cascaded, so they must not
+        // join a removed node.
+        let mut edit = graph.edit();
+        edit.add_edge(Edge::builder().source_id(1).target_id(3).build().unwrap()).remove_node(3);
+        assert!(matches!(edit.commit(), Err(GraphError::NodeNotFound { node_id: 3 })));
+
+        let mut edit = graph.edit();
+        edit.add_node(Node::builder().id(2).build().unwrap());
+        assert!(matches!(edit.commit(), Err(GraphError::DuplicateNodeId { node_id: 2 })));
+        let mut edit = graph.edit();
+        edit.add_node(Node::builder().id(4).build().unwrap())
+            .add_node(Node::builder().id(4).build().unwrap());
+        assert!(matches!(edit.commit(), Err(GraphError::DuplicateNodeId { node_id: 4 })));
+        assert_eq!((graph.num_nodes(), graph.num_edges()), (3, 3));
+        assert!(graph.validate().unwrap().is_valid());
+
+        // A removed node may be added back.
+        let mut edit = graph.edit();
+        edit.remove_node(3).add_node(Node::builder().id(3).build().unwrap());
+        edit.commit().unwrap();
+        assert_eq!((graph.num_nodes(), graph.num_edges()), (3, 1));
+    }
+
+    #[test]
+    fn test_weights_of_pending_edges() {
+        let mut graph = build_graph();
+        let mut edit = graph.edit();
+        edit.add_edge(Edge::builder().id(10u64).source_id(1).target_id(3).build().unwrap())
+            .set_weight(10, 4.0);
+        edit.commit().unwrap();
+        assert_eq!(graph.edge_by_id(10).unwrap().unwrap().weight, Some(4.0));
+
+        let mut edit = graph.edit();
+        edit.remove_edge(10).set_weight(10, 5.0);
+        assert!(matches!(edit.commit(), Err(GraphError::EdgeNotFound { edge_id: 10 })));
+        let mut edit = graph.edit();
+        edit.remove_node(3).set_weight(1, 5.0);
+        assert!(matches!(edit.commit(), Err(GraphError::EdgeNotFound { edge_id: 1 })));
+        assert_eq!(graph.num_edges(), 4);
+    }
+}
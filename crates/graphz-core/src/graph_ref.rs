@@ -0,0 +1,185 @@
+use crate::{
+    adjacency::AdjacencyIndex,
+    types::{EdgeId, NodeId, Weight},
+    Graph, GraphKind,
+};
+
+/// An edge followed from its source to its target.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeRef {
+    pub id: EdgeId,
+    pub source: NodeId,
+    pub target: NodeId,
+    // Empty for edges that weighted algorithms leave out
+    pub weight: Option<Weight>,
+}
+
+impl EdgeRef {
+    /// The same edge followed from its target to its source.
+    pub fn reversed(self) -> Self {
+        Self { source: self.target, target: self.source, ..self }
+    }
+}
+
+/// Read access to the structure of a graph.
+///
+/// Searches and predicates take any `GraphRef`, so they run on a [`Graph`],
+/// on the views over it, and on other graph backends alike. Backends only
+/// need to list their nodes and the edges of a node.
+pub trait GraphRef {
+    fn is_directed(&self) -> bool;
+
+    fn node_ids(&self) -> impl Iterator<Item = NodeId> + '_;
+
+    fn node_count(&self) -> usize {
+        self.node_ids().count()
+    }
+
+    /// Edges followed out of `node`, with `node` as their source.
+    ///
+    /// Undirected graphs follow every edge of `node`.
+    fn outgoing_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeRef> + '_;
+
+    /// Edges followed into `node`, with `node` as their target.
+    ///
+    /// Undirected graphs follow every edge of `node`.
+    fn incoming_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeRef> + '_;
+
+    fn neighbors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.outgoing_edges(node).map(|edge| edge.target)
+    }
+
+    fn predecessors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.incoming_edges(node).map(|edge| edge.source)
+    }
+
+    /// Weight of the lightest edge followed from `source` to `target`.
+    fn edge_weight(&self, source: NodeId, target: NodeId) -> Option<Weight> {
+        self.outgoing_edges(source)
+            .filter(|edge| edge.target == target)
+            .filter_map(|edge| edge.weight)
+            .min_by(Weight::total_cmp)
+    }
+}
+
+impl<G: GraphRef + ?Sized> GraphRef for &G {
+    fn is_directed(&self) -> bool {
+        (**self).is_directed()
+    }
+
+    fn node_ids(&self) -> impl Iterator<Item = NodeId> + '_ {
+        (**self).node_ids()
+    }
+
+    fn node_count(&self) -> usize {
+        (**self).node_count()
+    }
+
+    fn outgoing_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeRef> + '_ {
+        (**self).outgoing_edges(node)
+    }
+
+    fn incoming_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeRef> + '_ {
+        (**self).incoming_edges(node)
+    }
+
+    fn edge_weight(&self, source: NodeId, target: NodeId) -> Option<Weight> {
+        (**self).edge_weight(source, target)
+    }
+}
+
+/// Edges of `node` in `index`, keyed by the `node` end of the edge.
+fn index_edges(index: &AdjacencyIndex, node: NodeId) -> impl Iterator<Item = EdgeRef> + '_ {
+    index
+        .neighbors(node)
+        .iter()
+        .zip(index.weights(node))
+        .zip(index.edge_ids(node))
+        .map(move |((&target, &weight), &id)| EdgeRef { id, source: node, target, weight })
+}
+
+// The edge columns are checked on construction, so building the indexes
+// cannot fail.
+impl GraphRef for Graph {
+    fn is_directed(&self) -> bool {
+        self.kind() == GraphKind::Directed
+    }
+
+    fn node_ids(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.node_data().node_ids().unwrap().values()
+    }
+
+    fn node_count(&self) -> usize {
+        self.num_nodes()
+    }
+
+    fn outgoing_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeRef> + '_ {
+        index_edges(self.adjacency().unwrap(), node)
+    }
+
+    fn incoming_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeRef> + '_ {
+        index_edges(self.reverse_adjacency().unwrap(), node).map(EdgeRef::reversed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{a_search, breath_first_search, depth_first_search, dijkstra_search, GraphType};
+
+    /// A graph backend outside of Arrow: a plain list of weighted edges.
+    struct EdgeList {
+        nodes: Vec<NodeId>,
+        edges: Vec<(NodeId, NodeId, Weight)>,
+    }
+
+    impl GraphRef for EdgeList {
+        fn is_directed(&self) -> bool {
+            true
+        }
+
+        fn node_ids(&self) -> impl Iterator<Item = NodeId> + '_ {
+            self.nodes.iter().copied()
+        }
+
+        fn outgoing_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeRef> + '_ {
+            self.edges.iter().enumerate().filter(move |(_, edge)| edge.0 == node).map(
+                |(id, &(source, target, weight))| EdgeRef {
+                    id: id as EdgeId,
+                    source,
+                    target,
+                    weight: Some(weight),
+                },
+            )
+        }
+
+        fn incoming_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeRef> + '_ {
+            self.edges.iter().enumerate().filter(move |(_, edge)| edge.1 == node).map(
+                |(id, &(source, target, weight))| EdgeRef {
+                    id: id as EdgeId,
+                    source,
+                    target,
+                    weight: Some(weight),
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_other_backend() {
+        let graph = EdgeList {
+            nodes: vec![1, 2, 3, 4],
+            edges: vec![(1, 2, 1.0), (2, 3, 1.0), (1, 3, 5.0), (1, 3, 4.0), (3, 4, 1.0)],
+        };
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph.edge_weight(1, 3), Some(4.0));
+        assert_eq!(graph.edge_weight(3, 1), None);
+        assert_eq!(breath_first_search(&graph, 1, 4), Some(vec![1, 3, 4]));
+        assert!(depth_first_search(&graph, 1, 4).is_some());
+        assert_eq!(dijkstra_search(&graph, 1, 4), Some(vec![1, 2, 3, 4]));
+        assert_eq!(a_search(&graph, 1, 4), Some(vec![1, 2, 3, 4]));
+        assert!(graph.is_connected());
+        assert!(graph.is_acyclic());
+        assert!(!graph.is_complete());
+    }
+}
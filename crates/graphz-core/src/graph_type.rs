@@ -1,74 +1,148 @@
-use crate::{breath_first_search, types::NodeId, Graph};
-use std::collections::HashSet;
-
-impl Graph {
-    pub fn is_complete(&self) -> bool {
-        let num_nodes = self.num_nodes();
-        let num_edges = self.num_edges();
-        let expected_edges = (num_nodes * (num_nodes - 1)) / 2;
-
-        if expected_edges != num_edges {
+use crate::{graph_ref::GraphRef, types::NodeId, Graph};
+use std::collections::{HashMap, HashSet};
+
+/// Structural predicates, available on every [`GraphRef`].
+pub trait GraphType: GraphRef {
+    /// Returns true if every pair of distinct nodes is joined by an edge.
+    /// Directed graphs need an edge in each direction.
+    fn is_complete(&self) -> bool {
+        if self.node_count() < 2 {
             return false;
         }
+        let node_ids = self.node_ids().collect::<HashSet<_>>();
+
+        node_ids.iter().all(|&node_id| {
+            let neighbors = self
+                .neighbors(node_id)
+                .filter(|neighbor| *neighbor != node_id && node_ids.contains(neighbor))
+                .collect::<HashSet<_>>();
+            neighbors.len() == node_ids.len() - 1
+        })
+    }
 
-        if num_nodes == 1 {
+    /// Returns true if every node can be reached from every other node.
+    /// Edge directions are ignored, so directed graphs must be weakly connected.
+    fn is_connected(&self) -> bool {
+        let node_ids = self.node_ids().collect::<HashSet<_>>();
+        let Some(&start) = node_ids.iter().next() else {
             return false;
+        };
+
+        let mut visited = HashSet::from([start]);
+        let mut stack = vec![start];
+        while let Some(current) = stack.pop() {
+            let mut neighbors = self.neighbors(current).collect::<Vec<_>>();
+            if self.is_directed() {
+                neighbors.extend(self.predecessors(current));
+            }
+            for neighbor in neighbors {
+                if node_ids.contains(&neighbor) && visited.insert(neighbor) {
+                    stack.push(neighbor);
+                }
+            }
         }
+        visited.len() == node_ids.len()
+    }
+
+    fn is_acyclic(&self) -> bool {
+        match self.is_directed() {
+            true => is_directed_acyclic(self),
+            false => is_undirected_acyclic(self),
+        }
+    }
+}
+
+impl<G: GraphRef + ?Sized> GraphType for G {}
 
-        return true;
+// Inherent forwards, so the predicates need no trait import on a `Graph`.
+impl Graph {
+    /// See [`GraphType::is_complete`].
+    pub fn is_complete(&self) -> bool {
+        GraphType::is_complete(self)
     }
 
+    /// See [`GraphType::is_connected`].
     pub fn is_connected(&self) -> bool {
-        if self.num_nodes() == 0 {
-            return false;
-        }
-        let mut node_ids: Vec<NodeId> =
-            (0..self.num_nodes()).map(|idx| self.node_id(idx).unwrap()).collect();
-        node_ids.sort();
-
-        let result =
-            breath_first_search(self, *node_ids.first().unwrap(), *node_ids.last().unwrap());
-        println!("Result: {:?}", result);
-        result.is_some()
+        GraphType::is_connected(self)
     }
 
+    /// See [`GraphType::is_acyclic`].
     pub fn is_acyclic(&self) -> bool {
-        let mut visited = HashSet::new();
-        let mut stack = Vec::new();
-        let mut recursion_stack = HashSet::new();
-
-        for i in 0..self.num_nodes() {
-            let start_node_id = self.node_id(i).unwrap();
-            if !visited.contains(&start_node_id) {
-                stack.push(start_node_id);
-                recursion_stack.insert(start_node_id);
-
-                while let Some(current_node_id) = stack.pop() {
-                    if !visited.insert(current_node_id) {
-                        return false;
-                    }
-
-                    let neighbors = self.neighbors(current_node_id).unwrap();
-
-                    for neighbor in neighbors {
-                        if !visited.contains(&neighbor) {
-                            stack.push(neighbor);
-                            recursion_stack.insert(neighbor);
-                        } else if recursion_stack.contains(&neighbor) {
-                            return false;
-                        }
-                    }
-                }
-                recursion_stack.clear();
+        GraphType::is_acyclic(self)
+    }
+}
+
+fn is_directed_acyclic<G: GraphRef + ?Sized>(graph: &G) -> bool {
+    let mut finished = HashSet::new();
+    let mut on_stack = HashSet::new();
+
+    for start_node_id in graph.node_ids() {
+        if finished.contains(&start_node_id) {
+            continue;
+        }
+        // Each frame holds a node and its remaining neighbors.
+        let mut stack = vec![(start_node_id, graph.neighbors(start_node_id))];
+        on_stack.insert(start_node_id);
+
+        while let Some((current_node_id, neighbors)) = stack.last_mut() {
+            let Some(neighbor) = neighbors.next() else {
+                on_stack.remove(current_node_id);
+                finished.insert(*current_node_id);
+                stack.pop();
+                continue;
+            };
+
+            if on_stack.contains(&neighbor) {
+                return false;
             }
+            if !finished.contains(&neighbor) {
+                on_stack.insert(neighbor);
+                stack.push((neighbor, graph.neighbors(neighbor)));
+            }
+        }
+    }
+    true
+}
+
+fn is_undirected_acyclic<G: GraphRef + ?Sized>(graph: &G) -> bool {
+    // Union-find over the edges: an edge inside one component closes a cycle.
+    // Each edge is seen from both ends, so it is only joined once.
+    let mut parents = HashMap::new();
+    let mut joined = HashSet::new();
+    for node_id in graph.node_ids() {
+        for edge in graph.outgoing_edges(node_id) {
+            if !joined.insert(edge.id) {
+                continue;
+            }
+            let source_root = find_root(&mut parents, edge.source);
+            let target_root = find_root(&mut parents, edge.target);
+            if source_root == target_root {
+                return false;
+            }
+            parents.insert(source_root, target_root);
         }
-        true
     }
+    true
+}
+
+fn find_root(parents: &mut HashMap<NodeId, NodeId>, node_id: NodeId) -> NodeId {
+    let mut root = node_id;
+    while let Some(&parent) = parents.get(&root) {
+        root = parent;
+    }
+
+    let mut current = node_id;
+    while current != root {
+        let parent = parents[&current];
+        parents.insert(current, root);
+        current = parent;
+    }
+    root
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{edge::Edge, Graph};
+    use crate::{edge::Edge, Graph, GraphKind};
 
     #[test]
     fn test_is_complete() {
@@ -85,9 +159,27 @@ mod tests {
             Edge::builder().source_id(3).target_id(4).build().unwrap(),
         ];
 
-        let graph = Graph::builder().edges(edges).build().unwrap();
+        let graph =
+            Graph::builder().edges(edges.clone()).kind(GraphKind::Undirected).build().unwrap();
         assert_eq!(graph.num_nodes(), 5);
         assert!(graph.is_complete());
+
+        // The same edges only cover one direction of each pair.
+        let graph = Graph::builder().edges(edges.clone()).build().unwrap();
+        assert!(!graph.is_complete());
+
+        let mut both_directions = edges.clone();
+        for edge in edges {
+            both_directions.push(
+                Edge::builder()
+                    .source_id(edge.target_id)
+                    .target_id(edge.source_id)
+                    .build()
+                    .unwrap(),
+            );
+        }
+        let graph = Graph::builder().edges(both_directions).build().unwrap();
+        assert!(graph.is_complete());
     }
 
     #[test]
@@ -102,6 +194,14 @@ mod tests {
         let graph = Graph::builder().edges(edges).build().unwrap();
 
         assert!(graph.is_connected());
+
+        let edges = vec![
+            Edge::builder().source_id(0).target_id(1).build().unwrap(),
+            Edge::builder().source_id(2).target_id(1).build().unwrap(),
+            Edge::builder().source_id(3).target_id(4).build().unwrap(),
+        ];
+        let graph = Graph::builder().edges(edges).kind(GraphKind::Undirected).build().unwrap();
+        assert!(!graph.is_connected());
     }
 
     #[test]
@@ -124,4 +224,31 @@ mod tests {
         let graph_with_cycle = Graph::builder().edges(edges_with_cycle).build().unwrap();
         assert!(!graph_with_cycle.is_acyclic());
     }
+
+    #[test]
+    fn test_is_acyclic_diamond() {
+        let edges = vec![
+            Edge::builder().source_id(1).target_id(2).build().unwrap(),
+            Edge::builder().source_id(1).target_id(3).build().unwrap(),
+            Edge::builder().source_id(2).target_id(4).build().unwrap(),
+            Edge::builder().source_id(3).target_id(4).build().unwrap(),
+        ];
+        let graph = Graph::builder().edges(edges.clone()).build().unwrap();
+        assert!(graph.is_acyclic());
+
+        // Without directions the diamond is a cycle.
+        let graph = Graph::builder().edges(edges).kind(GraphKind::Undirected).build().unwrap();
+        assert!(!graph.is_acyclic());
+    }
+
+    #[test]
+    fn test_is_acyclic_undirected_tree() {
+        let edges = vec![
+            Edge::builder().source_id(1).target_id(2).build().unwrap(),
+            Edge::builder().source_id(1).target_id(3).build().unwrap(),
+            Edge::builder().source_id(4).target_id(2).build().unwrap(),
+        ];
+        let graph = Graph::builder().edges(edges).kind(GraphKind::Undirected).build().unwrap();
+        assert!(graph.is_acyclic());
+    }
 }
@@ -0,0 +1,188 @@
+use std::collections::{HashMap, HashSet};
+
+use arrow::array::Array;
+use snafu::prelude::*;
+
+use crate::{
+    graph::{EdgeDataSnafu, NodeDataSnafu},
+    graph_ref::EdgeRef,
+    property,
+    types::NodeId,
+    view::EdgeFiltered,
+    Graph, GraphError,
+};
+
+impl Graph {
+    /// Number of nodes with each label. Nodes without a label are not counted.
+    pub fn label_counts(&self) -> Result<HashMap<String, usize>, GraphError> {
+        if !self.node_data().has_labels() {
+            return Ok(HashMap::new());
+        }
+        let labels = self.node_data().labels().context(NodeDataSnafu {})?;
+        Ok(property::dictionary_counts(labels.chunks().iter().copied()))
+    }
+
+    /// Number of edges of each type. Edges without a type are not counted.
+    pub fn edge_type_counts(&self) -> Result<HashMap<String, usize>, GraphError> {
+        if !self.edge_data().has_edge_types() {
+            return Ok(HashMap::new());
+        }
+        let edge_types = self.edge_data().edge_types().context(EdgeDataSnafu {})?;
+        Ok(property::dictionary_counts(edge_types.chunks().iter().copied()))
+    }
+
+    /// Ids of the nodes labelled `label`.
+    pub fn nodes_with_label(&self, label: &str) -> Result<Vec<NodeId>, GraphError> {
+        if !self.node_data().has_labels() {
+            return Ok(Vec::new());
+        }
+        let labels = self.node_data().labels().context(NodeDataSnafu {})?;
+        let matches = property::dictionary_matches(labels.chunks().iter().copied(), &[label]);
+        let node_ids = self.node_data().node_ids().context(NodeDataSnafu {})?;
+        Ok(node_ids.values().zip(matches).filter(|(_, kept)| *kept).map(|(id, _)| id).collect())
+    }
+
+    /// Returns true for the edges, in row order, whose type is one of `edge_types`.
+    fn edge_type_mask(&self, edge_types: &[&str]) -> Result<Vec<bool>, GraphError> {
+        if !self.edge_data().has_edge_types() {
+            return Ok(vec![false; self.num_edges()]);
+        }
+        let column = self.edge_data().edge_types().context(EdgeDataSnafu {})?;
+        Ok(property::dictionary_matches(column.chunks().iter().copied(), edge_types))
+    }
+
+    /// Nodes reached from `node` over edges whose type is one of `edge_types`.
+    ///
+    /// Undirected graphs follow every edge of `node`.
+    pub fn neighbors_by_edge_type(
+        &self,
+        node: NodeId,
+        edge_types: &[&str],
+    ) -> Result<Vec<NodeId>, GraphError> {
+        if !self.edge_data().has_edge_types() {
+            return Ok(Vec::new());
+        }
+        let index = self.adjacency().context(EdgeDataSnafu {})?;
+        let column = self.edge_data().edge_types().context(EdgeDataSnafu {})?;
+        // Types are matched once per dictionary entry, then looked up by key.
+        let wanted_keys = column
+            .chunks()
+            .iter()
+            .map(|chunk| property::dictionary_wanted_keys(chunk, edge_types))
+            .collect::<Vec<_>>();
+        Ok(index
+            .neighbors(node)
+            .iter()
+            .zip(index.edges(node))
+            .filter(|(_, &row)| {
+                let (chunk, idx) = column.locate(row);
+                let keys = column.chunks()[chunk].keys();
+                keys.is_valid(idx) && wanted_keys[chunk][keys.value(idx) as usize]
+            })
+            .map(|(&target, _)| target)
+            .collect())
+    }
+
+    /// View of the graph following only the edges whose type is one of
+    /// `edge_types`, for restricting searches to those relations.
+    pub fn edge_type_view(
+        &self,
+        edge_types: &[&str],
+    ) -> Result<EdgeFiltered<&Graph, impl Fn(&EdgeRef) -> bool>, GraphError> {
+        let edge_ids = self.edge_data().edge_ids().context(EdgeDataSnafu {})?;
+        let kept = edge_ids
+            .values()
+            .zip(self.edge_type_mask(edge_types)?)
+            .filter(|(_, kept)| *kept)
+            .map(|(edge_id, _)| edge_id)
+            .collect::<HashSet<_>>();
+        Ok(EdgeFiltered::new(self, move |edge: &EdgeRef| kept.contains(&edge.id)))
+    }
+
+    /// Returns the graph with every node and the edges whose type is one of
+    /// `edge_types`.
+    pub fn edge_type_subgraph(&self, edge_types: &[&str]) -> Result<Graph, GraphError> {
+        self.subgraph(vec![true; self.num_nodes()], self.edge_type_mask(edge_types)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{breath_first_search, edge::Edge, node::Node, GraphRef};
+    use std::sync::Arc;
+
+    fn build_graph() -> Graph {
+        let nodes = vec![
+            Node::builder().id(1).label("User").build().unwrap(),
+            Node::builder().id(2).label("User").build().unwrap(),
+            Node::builder().id(3).label("Repo").build().unwrap(),
+            Node::builder().id(4).label("Repo").build().unwrap(),
+            Node::builder().id(5).build().unwrap(),
+        ];
+        let edge = |source_id, target_id, edge_type: &str| {
+            Edge::builder()
+                .source_id(source_id)
+                .target_id(target_id)
+                .weight(1.0)
+                .edge_type(edge_type)
+                .build()
+                .unwrap()
+        };
+        let edges = vec![
+            edge(1, 3, "OWNS"),
+            edge(1, 4, "STARS"),
+            edge(2, 4, "OWNS"),
+            edge(2, 1, "FOLLOWS"),
+            Edge::builder().source_id(4).target_id(5).weight(1.0).build().unwrap(),
+        ];
+        Graph::builder().nodes(nodes).edges(edges).build().unwrap()
+    }
+
+    #[test]
+    fn test_labels_and_edge_types() {
+        let mut graph = build_graph();
+        assert_eq!(
+            graph.label_counts().unwrap(),
+            HashMap::from([("User".to_string(), 2), ("Repo".to_string(), 2)])
+        );
+        assert_eq!(graph.nodes_with_label("Repo").unwrap(), vec![3, 4]);
+        assert_eq!(graph.node_label_by_id(5).unwrap(), None);
+        assert_eq!(graph.edge_type(1).unwrap(), Some("STARS"));
+        assert_eq!(graph.edge_data().edge(4).unwrap().edge_type, None);
+        // Edges of a type share its string.
+        let edges = graph.edges_iter().unwrap().collect::<Vec<_>>();
+        assert!(Arc::ptr_eq(
+            edges[0].edge_type.as_ref().unwrap(),
+            edges[2].edge_type.as_ref().unwrap()
+        ));
+
+        let mut edit = graph.edit();
+        edit.add_edge(
+            Edge::builder().source_id(2).target_id(3).edge_type("STARS").build().unwrap(),
+        );
+        edit.add_node(Node::builder().id(6).label("User").build().unwrap());
+        edit.commit().unwrap();
+        assert_eq!(graph.edge_data().record_batches().len(), 2);
+        assert_eq!(graph.edge_type_counts().unwrap()["STARS"], 2);
+        assert_eq!(graph.label_counts().unwrap()["User"], 3);
+        assert_eq!(graph.nodes_iter().unwrap().last().unwrap().label.as_deref(), Some("User"));
+        assert_eq!(graph.neighbors_by_edge_type(2, &["STARS", "FOLLOWS"]).unwrap(), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_relation_restricted_search() {
+        let graph = build_graph();
+        assert_eq!(breath_first_search(&graph, 2, 3), Some(vec![2, 1, 3]));
+
+        let owns = graph.edge_type_view(&["OWNS"]).unwrap();
+        assert_eq!(owns.neighbors(1).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(breath_first_search(&owns, 2, 3), None);
+        let social = graph.edge_type_view(&["OWNS", "FOLLOWS"]).unwrap();
+        assert_eq!(breath_first_search(&social, 2, 3), Some(vec![2, 1, 3]));
+
+        let subgraph = graph.edge_type_subgraph(&["OWNS"]).unwrap();
+        assert_eq!(subgraph.num_nodes(), 5);
+        assert_eq!(subgraph.num_edges(), 2);
+    }
+}
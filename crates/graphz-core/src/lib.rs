@@ -1,17 +1,47 @@
 mod a_search;
+mod adjacency;
 mod breath_first_search;
+mod chunked;
+mod convert;
 mod depth_first_search;
 mod dijkstra_search;
 mod graph;
+mod graph_mut;
+mod graph_ref;
 mod graph_type;
+mod heterogeneous;
+mod node_link;
+mod position;
+mod property;
+mod set_ops;
+mod subgraph;
+mod temporal;
+mod transform;
 mod types;
+mod validate;
+mod view;
 
 pub mod edge;
+pub mod generators;
 pub mod node;
 
 pub use a_search::*;
+pub use adjacency::*;
 pub use arrow;
 pub use breath_first_search::*;
+pub use chunked::ChunkedColumn;
 pub use depth_first_search::*;
 pub use dijkstra_search::*;
+pub use edge::MissingWeight;
 pub use graph::*;
+pub use graph_mut::*;
+pub use graph_ref::*;
+pub use graph_type::*;
+pub use position::*;
+pub use property::{DictionaryStringArray, PropertyValue};
+pub use set_ops::*;
+pub use temporal::*;
+pub use transform::*;
+pub use types::*;
+pub use validate::*;
+pub use view::*;
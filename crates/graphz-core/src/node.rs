@@ -1,19 +1,33 @@
 use snafu::prelude::*;
-use std::{error::Error, sync::Arc};
+use std::{collections::HashSet, error::Error, str::FromStr, sync::Arc};
 
 use arrow::{
-    array::{Array, PrimitiveArray, RecordBatch},
-    datatypes::{DataType, Field, Float64Type, Schema, UInt32Type},
+    array::{
+        Array, ArrayRef, BooleanArray, FixedSizeListArray, PrimitiveArray, RecordBatch, StringArray,
+    },
+    buffer::NullBuffer,
+    datatypes::{ArrowPrimitiveType, DataType, Field, Float64Type, Schema, SchemaRef},
+    error::ArrowError,
 };
 use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
 
-use crate::types::{NodeId, Position, Weight};
+use crate::{
+    chunked::{ChunkedColumn, ChunkedRecordBatch},
+    position::Position,
+    property::{self, DictionaryStringArray, PropertyValue},
+    types::{NodeId, NodeIdArray, NodeIdType, Weight},
+};
 
 #[derive(Debug, Snafu)]
 pub enum NodeDataError {
     IndexOutOfBounds,
     ColumnNotFound,
     ColumnTypeMismatch { data_type: String },
+    PositionDimensionMismatch { dimension: usize },
+    FailedToAddNodes { source: ArrowError },
+    InvalidRecordBatches { source: ArrowError },
+    DuplicateKey { key: String },
 }
 
 type Result<T, E = NodeDataError> = std::result::Result<T, E>;
@@ -27,19 +41,33 @@ pub enum Attribute {
     #[strum(serialize = "weight")]
     Weight,
     // Position of the node
-    // This is a list of floats, representing the x, y, z coordinates of the node
-    // in a 3D space
+    // This is a fixed-size list of 2 or 3 floats, representing the x, y(, z)
+    // coordinates of the node in a 2D or 3D space
     #[strum(serialize = "position")]
     Position,
+    // Optional external key of the node, such as a user or package name
+    #[strum(serialize = "key")]
+    Key,
+    // Optional dictionary-encoded label of the node, such as User or Repo
+    #[strum(serialize = "label")]
+    Label,
 }
 
-#[derive(Debug, Clone, PartialEq, Builder)]
+#[derive(Debug, Clone, PartialEq, Builder, Serialize, Deserialize)]
 pub struct Node {
     pub id: NodeId,
     #[builder(setter(into, strip_option), default)]
+    #[serde(default)]
+    pub key: Option<String>,
+    #[builder(setter(into, strip_option), default)]
+    #[serde(default)]
     pub position: Option<Position>,
     #[builder(setter(into, strip_option), default)]
+    #[serde(default)]
     pub weight: Option<Weight>,
+    #[builder(setter(into, strip_option), default)]
+    #[serde(default)]
+    pub label: Option<String>,
 }
 
 impl Node {
@@ -48,70 +76,215 @@ impl Node {
     }
 }
 
+/// Nodes stored as one or more record batches sharing a schema.
+///
+/// Appending nodes adds a chunk, so the existing rows are not copied.
 #[derive(Debug, Clone, PartialEq)]
-pub struct NodeRecordBatch(pub(crate) RecordBatch);
+pub struct NodeRecordBatch(ChunkedRecordBatch);
 
 impl NodeRecordBatch {
+    /// Schema with 3D positions.
     pub fn schema() -> Schema {
+        Self::schema_with_dimension(3)
+    }
+
+    /// Schema with positions of `dimension` coordinates, either 2 or 3.
+    pub fn schema_with_dimension(dimension: i32) -> Schema {
         Schema::new(vec![
-            Field::new(Attribute::Node.as_ref(), DataType::UInt32, false),
+            Field::new(Attribute::Node.as_ref(), NodeIdType::DATA_TYPE, false),
             Field::new(Attribute::Weight.as_ref(), DataType::Float64, true),
-            Field::new(Attribute::Position.as_ref(), DataType::Float64, true),
+            Field::new(
+                Attribute::Position.as_ref(),
+                DataType::new_fixed_size_list(DataType::Float64, dimension, false),
+                true,
+            ),
         ])
     }
 
-    pub fn record_batch(&self) -> &RecordBatch {
-        &self.0
+    /// Builds nodes with the given ids and no weight, position or key.
+    pub fn from_node_ids(node_ids: Vec<NodeId>) -> Self {
+        let num_nodes = node_ids.len();
+        let positions = FixedSizeListArray::new_null(
+            Arc::new(Field::new_list_field(DataType::Float64, false)),
+            3,
+            num_nodes,
+        );
+        let record_batch = RecordBatch::try_new(
+            Arc::new(Self::schema()),
+            vec![
+                Arc::new(NodeIdArray::from(node_ids)),
+                Arc::new(PrimitiveArray::<Float64Type>::new_null(num_nodes)),
+                Arc::new(positions),
+            ],
+        )
+        .unwrap();
+        Self(record_batch.into())
+    }
+
+    /// Builds nodes from record batches with the fields of `schema`.
+    pub fn try_from_record_batches(
+        schema: SchemaRef,
+        record_batches: Vec<RecordBatch>,
+    ) -> Result<Self> {
+        let record_batch = ChunkedRecordBatch::try_new(schema, record_batches)
+            .context(InvalidRecordBatchesSnafu {})?;
+        Ok(Self(record_batch))
+    }
+
+    pub fn schema_ref(&self) -> &SchemaRef {
+        self.0.schema_ref()
+    }
+
+    /// The chunks holding the nodes, in row order.
+    pub fn record_batches(&self) -> &[RecordBatch] {
+        self.0.chunks()
+    }
+
+    /// Returns the nodes as one record batch, concatenating the chunks if
+    /// there is more than one.
+    pub fn to_record_batch(&self) -> RecordBatch {
+        self.0.to_record_batch()
+    }
+
+    /// Merges the chunks into one.
+    pub fn compact(&mut self) {
+        self.0.compact()
     }
 
     pub fn num_nodes(&self) -> usize {
-        self.record_batch().num_rows()
+        self.0.num_rows()
     }
 
-    pub fn node_id(&self, idx: usize) -> Result<NodeId> {
+    fn column<T: Array + 'static>(&self, name: &str) -> Result<ChunkedColumn<'_, T>> {
+        let (idx, field) =
+            self.schema_ref().column_with_name(name).ok_or(NodeDataError::ColumnNotFound)?;
+        self.0.column(idx).ok_or_else(|| NodeDataError::ColumnTypeMismatch {
+            data_type: field.data_type().to_string(),
+        })
+    }
+
+    pub fn node_ids(&self) -> Result<ChunkedColumn<'_, NodeIdArray>> {
+        self.column(Attribute::Node.as_ref())
+    }
+
+    pub fn weights(&self) -> Result<ChunkedColumn<'_, PrimitiveArray<Float64Type>>> {
+        self.column(Attribute::Weight.as_ref())
+    }
+
+    pub fn positions(&self) -> Result<ChunkedColumn<'_, FixedSizeListArray>> {
+        self.dimension()?;
+        self.column(Attribute::Position.as_ref())
+    }
+
+    /// Number of coordinates of the positions, either 2 or 3.
+    pub fn dimension(&self) -> Result<usize> {
+        let field = self
+            .schema_ref()
+            .field_with_name(Attribute::Position.as_ref())
+            .map_err(|_| NodeDataError::ColumnNotFound)?;
+        match field.data_type() {
+            DataType::FixedSizeList(_, dimension @ (2 | 3)) => Ok(*dimension as usize),
+            data_type => {
+                Err(NodeDataError::ColumnTypeMismatch { data_type: data_type.to_string() })
+            }
+        }
+    }
+
+    /// Names of the user-defined property columns.
+    pub fn property_names(&self) -> Vec<&str> {
+        property::property_names(self.schema_ref(), &Self::schema())
+            .into_iter()
+            .filter(|name| Attribute::from_str(name).is_err())
+            .collect()
+    }
+
+    /// Returns true if the nodes carry the optional key column.
+    pub fn has_keys(&self) -> bool {
+        self.schema_ref().column_with_name(Attribute::Key.as_ref()).is_some()
+    }
+
+    pub fn keys(&self) -> Result<ChunkedColumn<'_, StringArray>> {
+        self.column(Attribute::Key.as_ref())
+    }
+
+    pub fn key(&self, idx: usize) -> Result<Option<&str>> {
         if idx >= self.num_nodes() {
             return Err(NodeDataError::IndexOutOfBounds);
         }
+        let (keys, idx) = self.keys()?.chunk_at(idx);
+        Ok(keys.is_valid(idx).then(|| keys.value(idx)))
+    }
+
+    /// Fails if two nodes share a key.
+    pub(crate) fn check_unique_keys(&self) -> Result<()> {
+        if !self.has_keys() {
+            return Ok(());
+        }
+        let keys = self.keys()?;
+        let mut seen = HashSet::with_capacity(self.num_nodes());
+        match keys
+            .chunks()
+            .iter()
+            .flat_map(|keys| keys.iter())
+            .flatten()
+            .find(|key| !seen.insert(*key))
+        {
+            Some(key) => Err(NodeDataError::DuplicateKey { key: key.to_string() }),
+            None => Ok(()),
+        }
+    }
 
-        let column = self
-            .record_batch()
-            .column_by_name(Attribute::Node.as_ref())
-            .ok_or(NodeDataError::ColumnNotFound)?;
-        let data = column.as_any().downcast_ref::<PrimitiveArray<UInt32Type>>().ok_or(
-            NodeDataError::ColumnTypeMismatch { data_type: column.data_type().to_string() },
-        )?;
+    /// Returns true if the nodes carry the optional label column.
+    pub fn has_labels(&self) -> bool {
+        property::has_dictionary_column(self.schema_ref(), Attribute::Label.as_ref())
+    }
 
-        Ok(data.value(idx))
+    pub fn labels(&self) -> Result<ChunkedColumn<'_, DictionaryStringArray>> {
+        self.column(Attribute::Label.as_ref())
     }
 
-    pub fn weight(&self, idx: usize) -> Result<Option<Weight>> {
+    /// Reads the label of the node at `idx`, `None` if the nodes have no labels.
+    pub fn label(&self, idx: usize) -> Result<Option<&str>> {
         if idx >= self.num_nodes() {
             return Err(NodeDataError::IndexOutOfBounds);
         }
-        let column = self
-            .record_batch()
-            .column_by_name(Attribute::Weight.as_ref())
-            .ok_or(NodeDataError::ColumnNotFound)?;
-        let data = column.as_any().downcast_ref::<PrimitiveArray<Float64Type>>().ok_or(
-            NodeDataError::ColumnTypeMismatch { data_type: column.data_type().to_string() },
-        )?;
+        if !self.has_labels() {
+            return Ok(None);
+        }
+        let (labels, idx) = self.labels()?.chunk_at(idx);
+        Ok(property::dictionary_value(labels, idx))
+    }
 
-        Ok(Some(data.value(idx)))
+    /// Reads the value of the property column `name` for the node at `idx`.
+    pub fn property<T: PropertyValue>(&self, idx: usize, name: &str) -> Result<Option<T>> {
+        if idx >= self.num_nodes() {
+            return Err(NodeDataError::IndexOutOfBounds);
+        }
+        let (data, idx) = self.column::<T::Array>(name)?.chunk_at(idx);
+        Ok(data.is_valid(idx).then(|| T::value(data, idx)))
     }
 
-    pub fn position(&self, idx: usize) -> Result<Option<Position>> {
+    pub fn node_id(&self, idx: usize) -> Result<NodeId> {
+        if idx >= self.num_nodes() {
+            return Err(NodeDataError::IndexOutOfBounds);
+        }
+        Ok(self.node_ids()?.value(idx))
+    }
+
+    pub fn weight(&self, idx: usize) -> Result<Option<Weight>> {
         if idx >= self.num_nodes() {
             return Err(NodeDataError::IndexOutOfBounds);
         }
-        let column = self
-            .record_batch()
-            .column_by_name(Attribute::Position.as_ref())
-            .ok_or(NodeDataError::ColumnNotFound)?;
-        let data = column.as_any().downcast_ref::<PrimitiveArray<Float64Type>>().ok_or(
-            NodeDataError::ColumnTypeMismatch { data_type: column.data_type().to_string() },
-        )?;
+        let (weights, idx) = self.weights()?.chunk_at(idx);
+        Ok(weights.is_valid(idx).then(|| weights.value(idx)))
+    }
 
-        Ok(Some(data.value(idx)))
+    pub fn position(&self, idx: usize) -> Result<Option<Position>> {
+        if idx >= self.num_nodes() {
+            return Err(NodeDataError::IndexOutOfBounds);
+        }
+        let (positions, idx) = self.positions()?.chunk_at(idx);
+        Ok(position_at(positions, position_values(positions)?, idx))
     }
 
     pub fn node(&self, idx: usize) -> Result<Node> {
@@ -119,48 +292,175 @@ impl NodeRecordBatch {
             return Err(NodeDataError::IndexOutOfBounds);
         }
         let node_id = self.node_id(idx)?;
+        let key = match self.has_keys() {
+            true => self.key(idx)?.map(str::to_string),
+            false => None,
+        };
         let weight = self.weight(idx)?;
         let position = self.position(idx)?;
-        Ok(Node { id: node_id, weight, position })
+        let label = self.label(idx)?.map(str::to_string);
+        Ok(Node { id: node_id, key, weight, position, label })
     }
 
-    pub fn nodes(&self) -> Result<Vec<Node>, Box<dyn Error>> {
-        let mut nodes = Vec::with_capacity(self.num_nodes());
-        for idx in 0..self.num_nodes() {
-            let node = self.node(idx)?;
-            nodes.push(node);
+    /// Appends nodes as a new chunk. Property columns are null for the new
+    /// nodes.
+    pub fn add_nodes(&mut self, nodes: Vec<Node>) -> Result<()> {
+        let dimension = self.dimension()?;
+        let has_3d_positions =
+            nodes.iter().any(|node| node.position.is_some_and(|p| p.z.is_some()));
+        if dimension < 3 && has_3d_positions {
+            return Err(NodeDataError::PositionDimensionMismatch { dimension });
         }
-        Ok(nodes)
+        let new_nodes = nodes_to_record_batch(&nodes, dimension);
+        self.0.push(new_nodes).context(FailedToAddNodesSnafu {})
+    }
+
+    /// Appends the chunks of `other`.
+    pub(crate) fn append(&mut self, other: &Self) -> Result<(), ArrowError> {
+        other.record_batches().iter().try_for_each(|chunk| self.0.push(chunk.clone()))
     }
+
+    /// Keeps the nodes where `keep` is true.
+    pub(crate) fn filter(&self, keep: &BooleanArray) -> Result<Self, ArrowError> {
+        Ok(Self(self.0.filter(keep)?))
+    }
+
+    /// Iterates over the nodes in row order, downcasting each column once
+    /// per chunk.
+    pub fn nodes_iter(&self) -> Result<impl Iterator<Item = Node> + '_> {
+        let node_ids = self.node_ids()?;
+        let weights = self.weights()?;
+        let positions = self.positions()?;
+        let keys = match self.has_keys() {
+            true => Some(self.keys()?),
+            false => None,
+        };
+        let labels = match self.has_labels() {
+            true => Some(self.labels()?),
+            false => None,
+        };
+        let chunks = (0..node_ids.chunks().len())
+            .map(|chunk| {
+                let chunk_positions = positions.chunks()[chunk];
+                Ok((
+                    node_ids.chunks()[chunk],
+                    weights.chunks()[chunk],
+                    chunk_positions,
+                    position_values(chunk_positions)?,
+                    keys.as_ref().map(|keys| keys.chunks()[chunk]),
+                    labels.as_ref().map(|labels| labels.chunks()[chunk]),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(chunks.into_iter().flat_map(
+            |(node_ids, weights, positions, coordinates, keys, labels)| {
+                (0..node_ids.len()).map(move |idx| Node {
+                    id: node_ids.value(idx),
+                    key: keys
+                        .and_then(|keys| keys.is_valid(idx).then(|| keys.value(idx).to_string())),
+                    weight: weights.is_valid(idx).then(|| weights.value(idx)),
+                    position: position_at(positions, coordinates, idx),
+                    label: labels
+                        .and_then(|labels| property::dictionary_value(labels, idx))
+                        .map(str::to_string),
+                })
+            },
+        ))
+    }
+
+    pub fn nodes(&self) -> Result<Vec<Node>, Box<dyn Error>> {
+        Ok(self.nodes_iter()?.collect())
+    }
+}
+
+/// Coordinates of the positions, stored flat in the child array of the list.
+fn position_values(positions: &FixedSizeListArray) -> Result<&PrimitiveArray<Float64Type>> {
+    let values = positions.values();
+    values.as_any().downcast_ref::<PrimitiveArray<Float64Type>>().ok_or_else(|| {
+        NodeDataError::ColumnTypeMismatch { data_type: values.data_type().to_string() }
+    })
+}
+
+fn position_at(
+    positions: &FixedSizeListArray,
+    coordinates: &PrimitiveArray<Float64Type>,
+    idx: usize,
+) -> Option<Position> {
+    if positions.is_null(idx) {
+        return None;
+    }
+    let offset = positions.value_offset(idx) as usize;
+    let coordinate = |axis: usize| coordinates.value(offset + axis);
+    Some(match positions.value_length() {
+        2 => Position::new_2d(coordinate(0), coordinate(1)),
+        3 => Position::new_3d(coordinate(0), coordinate(1), coordinate(2)),
+        // Other sizes are rejected with the schema by `NodeRecordBatch::dimension`.
+        _ => return None,
+    })
 }
 
 impl From<RecordBatch> for NodeRecordBatch {
     fn from(record_batch: RecordBatch) -> Self {
-        NodeRecordBatch(record_batch)
+        NodeRecordBatch(record_batch.into())
     }
 }
 
+/// Number of position coordinates able to hold the positions of `nodes`.
+fn position_dimension(nodes: &[Node]) -> usize {
+    let positions = nodes.iter().filter_map(|node| node.position);
+    match positions.clone().all(|position| position.z.is_none()) && positions.count() > 0 {
+        true => 2,
+        false => 3,
+    }
+}
+
+/// Builds a record batch of the nodes with positions of `dimension`
+/// coordinates. The key and label columns are only added when at least one
+/// node has a key or a label.
+fn nodes_to_record_batch(nodes: &[Node], dimension: usize) -> RecordBatch {
+    let mut node_ids = Vec::with_capacity(nodes.len());
+    let mut keys = Vec::with_capacity(nodes.len());
+    let mut labels = Vec::with_capacity(nodes.len());
+    let mut weights = Vec::with_capacity(nodes.len());
+    let mut coordinates = Vec::with_capacity(nodes.len() * dimension);
+    for node in nodes {
+        node_ids.push(node.id);
+        keys.push(node.key.as_deref());
+        labels.push(node.label.as_deref());
+        weights.push(node.weight);
+        let position = node.position.map(|position| position.coordinates()).unwrap_or_default();
+        coordinates.extend_from_slice(&position[..dimension]);
+    }
+    let positions = FixedSizeListArray::new(
+        Arc::new(Field::new_list_field(DataType::Float64, false)),
+        dimension as i32,
+        Arc::new(PrimitiveArray::<Float64Type>::from(coordinates)),
+        Some(NullBuffer::from_iter(nodes.iter().map(|node| node.position.is_some()))),
+    );
+
+    let mut fields = NodeRecordBatch::schema_with_dimension(dimension as i32).fields().to_vec();
+    let mut columns: Vec<ArrayRef> = vec![
+        Arc::new(NodeIdArray::from(node_ids)),
+        Arc::new(PrimitiveArray::<Float64Type>::from(weights)),
+        Arc::new(positions),
+    ];
+    if keys.iter().any(Option::is_some) {
+        fields.push(Arc::new(Field::new(Attribute::Key.as_ref(), DataType::Utf8, true)));
+        columns.push(Arc::new(StringArray::from(keys)));
+    }
+    if labels.iter().any(Option::is_some) {
+        let data_type = property::dictionary_string_type();
+        fields.push(Arc::new(Field::new(Attribute::Label.as_ref(), data_type, true)));
+        columns.push(Arc::new(labels.into_iter().collect::<DictionaryStringArray>()));
+    }
+
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap()
+}
+
 impl From<Vec<Node>> for NodeRecordBatch {
+    /// Positions are stored in 2D when every given position is 2D, and in 3D
+    /// otherwise, with a zero z coordinate for 2D positions.
     fn from(nodes: Vec<Node>) -> Self {
-        let mut node_ids = Vec::new();
-        let mut weights = Vec::new();
-        let mut positions = Vec::new();
-        for node in nodes {
-            node_ids.push(node.id);
-            weights.push(node.weight);
-            positions.push(node.position);
-        }
-
-        let schema = Self::schema();
-        let record_batch = RecordBatch::try_new(
-            Arc::new(schema),
-            vec![
-                Arc::new(PrimitiveArray::<UInt32Type>::from(node_ids)),
-                Arc::new(PrimitiveArray::<Float64Type>::from(weights)),
-                Arc::new(PrimitiveArray::<Float64Type>::from(positions)),
-            ],
-        )
-        .unwrap();
-        NodeRecordBatch(record_batch)
+        NodeRecordBatch(nodes_to_record_batch(&nodes, position_dimension(&nodes)).into())
     }
 }
@@ -0,0 +1,91 @@
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    edge::{Edge, EdgeColumns, EdgeRecordBatch},
+    node::{Node, NodeRecordBatch},
+    Graph, GraphKind,
+};
+
+/// Node-link shape of a graph: its kind, a list of nodes and a list of edges.
+///
+/// Property columns are not part of it.
+#[derive(Serialize, Deserialize)]
+struct NodeLink {
+    #[serde(default)]
+    kind: GraphKind,
+    // Inferred from the ends of the edges if missing
+    #[serde(default)]
+    nodes: Option<Vec<Node>>,
+    #[serde(default)]
+    edges: Vec<Edge>,
+}
+
+impl Serialize for Graph {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let nodes = self.nodes_iter().map_err(ser::Error::custom)?.collect();
+        let edges = self.edges_iter().map_err(ser::Error::custom)?.collect();
+        NodeLink { kind: self.kind(), nodes: Some(nodes), edges }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Graph {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let NodeLink { kind, nodes, edges } = NodeLink::deserialize(deserializer)?;
+        let graph = match nodes {
+            Some(nodes) => {
+                let edges = edges.into_iter().collect::<EdgeColumns>();
+                let edges = EdgeRecordBatch::try_from(edges).map_err(de::Error::custom)?;
+                Graph::from_arrow_record_batches(
+                    NodeRecordBatch::from(nodes),
+                    edges.with_kind(kind),
+                )
+            }
+            None => Graph::from_edges(edges, kind),
+        };
+        graph.map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_link_round_trip() {
+        let nodes = vec![
+            Node::builder().id(1).key("a").position((0.0, 1.0)).weight(1.0).build().unwrap(),
+            Node::builder().id(2).weight(2.0).build().unwrap(),
+        ];
+        let edges = vec![Edge::builder().source_id(1).target_id(2).weight(0.5).build().unwrap()];
+        let graph =
+            Graph::builder().nodes(nodes).edges(edges).kind(GraphKind::Undirected).build().unwrap();
+
+        let json = serde_json::to_string(&graph).unwrap();
+        assert_eq!(
+            json,
+            r#"{"kind":"undirected","nodes":[{"id":1,"key":"a","position":{"x":0.0,"y":1.0,"z":null},"weight":1.0,"label":null},{"id":2,"key":null,"position":null,"weight":2.0,"label":null}],"edges":[{"id":0,"source_id":1,"target_id":2,"weight":0.5,"timestamp":null,"valid_from":null,"valid_to":null,"edge_type":null}]}"#
+        );
+        let parsed = serde_json::from_str::<Graph>(&json).unwrap();
+        assert_eq!(parsed.kind(), GraphKind::Undirected);
+        assert!(parsed.nodes_iter().unwrap().eq(graph.nodes_iter().unwrap()));
+        assert!(parsed.edges_iter().unwrap().eq(graph.edges_iter().unwrap()));
+
+        // Missing fields are serialized, so formats that are not
+        // self-describing can read them back.
+        let bytes = bincode::serialize(&graph).unwrap();
+        let parsed = bincode::deserialize::<Graph>(&bytes).unwrap();
+        assert_eq!(parsed.kind(), GraphKind::Undirected);
+        assert!(parsed.nodes_iter().unwrap().eq(graph.nodes_iter().unwrap()));
+        assert!(parsed.edges_iter().unwrap().eq(graph.edges_iter().unwrap()));
+    }
+
+    #[test]
+    fn test_inferred_nodes() {
+        let json = r#"{"edges":[{"source_id":3,"target_id":4,"timestamp":7}]}"#;
+        let graph = serde_json::from_str::<Graph>(json).unwrap();
+        assert_eq!(graph.kind(), GraphKind::Directed);
+        assert_eq!(graph.nodes_iter().unwrap().map(|node| node.id).collect::<Vec<_>>(), [3, 4]);
+        assert_eq!(graph.edges_iter().unwrap().next().unwrap().timestamp, Some(7));
+        assert!(serde_json::from_str::<Graph>(r#"{"edges":[{"source_id":3}]}"#).is_err());
+    }
+}
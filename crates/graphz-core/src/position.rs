@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+/// Coordinates of a node in a 2D or 3D space.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+    // Only set for positions in a 3D space
+    #[serde(default)]
+    pub z: Option<f64>,
+}
+
+impl Position {
+    pub fn new_2d(x: f64, y: f64) -> Self {
+        Self { x, y, z: None }
+    }
+
+    pub fn new_3d(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z: Some(z) }
+    }
+
+    pub fn dimension(&self) -> usize {
+        match self.z {
+            Some(_) => 3,
+            None => 2,
+        }
+    }
+
+    /// Returns the coordinates, with a zero z coordinate for 2D positions.
+    pub fn coordinates(&self) -> [f64; 3] {
+        [self.x, self.y, self.z.unwrap_or_default()]
+    }
+
+    /// Euclidean distance to `other`, placing 2D positions on the z = 0 plane.
+    pub fn distance(&self, other: &Position) -> f64 {
+        self.coordinates()
+            .iter()
+            .zip(other.coordinates())
+            .map(|(a, b)| (a - b).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+impl From<(f64, f64)> for Position {
+    fn from((x, y): (f64, f64)) -> Self {
+        Self::new_2d(x, y)
+    }
+}
+
+impl From<(f64, f64, f64)> for Position {
+    fn from((x, y, z): (f64, f64, f64)) -> Self {
+        Self::new_3d(x, y, z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        let origin = Position::new_2d(0.0, 0.0);
+        assert_eq!(origin.distance(&Position::new_2d(3.0, 4.0)), 5.0);
+        assert_eq!(origin.distance(&Position::new_3d(0.0, 3.0, 4.0)), 5.0);
+        assert_eq!(Position::from((1.0, 2.0, 3.0)).dimension(), 3);
+        assert_eq!(Position::from((1.0, 2.0)).dimension(), 2);
+    }
+}
@@ -0,0 +1,182 @@
+use arrow::{
+    array::{
+        new_null_array, Array, ArrayAccessor, AsArray, BooleanArray, DictionaryArray,
+        PrimitiveArray, RecordBatch, StringArray,
+    },
+    datatypes::{
+        DataType, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, Schema,
+        SchemaRef, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+    },
+    error::ArrowError,
+};
+use std::{collections::HashMap, sync::Arc};
+
+/// Dictionary-encoded strings, as stored in the node label and edge type
+/// columns.
+pub type DictionaryStringArray = DictionaryArray<Int32Type>;
+
+/// A Rust value stored in a property column.
+///
+/// `Array` is the Arrow array type a column must have to be read as `Self`.
+pub trait PropertyValue: Sized {
+    type Array: Array + 'static;
+
+    fn value(array: &Self::Array, idx: usize) -> Self;
+}
+
+macro_rules! impl_primitive_property_value {
+    ($($native:ty => $arrow:ty),* $(,)?) => {
+        $(
+            impl PropertyValue for $native {
+                type Array = PrimitiveArray<$arrow>;
+
+                fn value(array: &Self::Array, idx: usize) -> Self {
+                    array.value(idx)
+                }
+            }
+        )*
+    };
+}
+
+impl_primitive_property_value!(
+    i8 => Int8Type,
+    i16 => Int16Type,
+    i32 => Int32Type,
+    i64 => Int64Type,
+    u8 => UInt8Type,
+    u16 => UInt16Type,
+    u32 => UInt32Type,
+    u64 => UInt64Type,
+    f32 => Float32Type,
+    f64 => Float64Type,
+);
+
+impl PropertyValue for bool {
+    type Array = BooleanArray;
+
+    fn value(array: &Self::Array, idx: usize) -> Self {
+        array.value(idx)
+    }
+}
+
+impl PropertyValue for String {
+    type Array = StringArray;
+
+    fn value(array: &Self::Array, idx: usize) -> Self {
+        array.value(idx).to_string()
+    }
+}
+
+/// Returns true if `schema` has every field of `required` with the same data
+/// type, and without nulls where `required` forbids them. Other fields are
+/// allowed and treated as properties.
+pub(crate) fn has_required_fields(schema: &Schema, required: &Schema) -> bool {
+    required.fields().iter().all(|required_field| {
+        schema.field_with_name(required_field.name()).is_ok_and(|field| {
+            field.data_type() == required_field.data_type()
+                && (required_field.is_nullable() || !field.is_nullable())
+        })
+    })
+}
+
+/// Names of the fields of `schema` that are not part of `required`.
+pub(crate) fn property_names<'a>(schema: &'a Schema, required: &Schema) -> Vec<&'a str> {
+    schema
+        .fields()
+        .iter()
+        .filter(|field| required.field_with_name(field.name()).is_err())
+        .map(|field| field.name().as_str())
+        .collect()
+}
+
+/// Arrow type of [`DictionaryStringArray`] columns.
+pub(crate) fn dictionary_string_type() -> DataType {
+    DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8))
+}
+
+/// Returns true if `schema` has the dictionary-encoded string column `name`.
+/// A plain string column of that name is left to be read as a property.
+pub(crate) fn has_dictionary_column(schema: &Schema, name: &str) -> bool {
+    schema.field_with_name(name).is_ok_and(|field| field.data_type() == &dictionary_string_type())
+}
+
+/// Reads row `idx` of a dictionary-encoded string array.
+pub(crate) fn dictionary_value(array: &DictionaryStringArray, idx: usize) -> Option<&str> {
+    let values = array.downcast_dict::<StringArray>()?;
+    values.is_valid(idx).then(|| values.value(idx))
+}
+
+/// Values of every row of a dictionary-encoded string array, with `None`
+/// for nulls. Each dictionary entry is allocated once and shared by its rows.
+pub(crate) fn dictionary_values(array: &DictionaryStringArray) -> Vec<Option<Arc<str>>> {
+    let Some(values) = array.values().as_string_opt::<i32>() else {
+        return vec![None; array.len()];
+    };
+    let values = values.iter().map(|value| value.map(Arc::from)).collect::<Vec<_>>();
+    array.keys().iter().map(|key| key.and_then(|key| values[key as usize].clone())).collect()
+}
+
+/// Whether each entry of the dictionary of `array` is one of `wanted`.
+pub(crate) fn dictionary_wanted_keys(array: &DictionaryStringArray, wanted: &[&str]) -> Vec<bool> {
+    match array.values().as_string_opt::<i32>() {
+        Some(values) => {
+            values.iter().map(|value| value.is_some_and(|value| wanted.contains(&value))).collect()
+        }
+        None => vec![false; array.values().len()],
+    }
+}
+
+/// Number of rows holding each string of dictionary-encoded string arrays.
+pub(crate) fn dictionary_counts<'a>(
+    arrays: impl IntoIterator<Item = &'a DictionaryStringArray>,
+) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for array in arrays {
+        let Some(values) = array.values().as_string_opt::<i32>() else {
+            continue;
+        };
+        // Counted per dictionary entry first, so each string is only copied once.
+        let mut key_counts = vec![0; values.len()];
+        for key in array.keys().iter().flatten() {
+            key_counts[key as usize] += 1;
+        }
+        for (key, count) in key_counts.into_iter().enumerate() {
+            if count > 0 && values.is_valid(key) {
+                *counts.entry(values.value(key).to_string()).or_default() += count;
+            }
+        }
+    }
+    counts
+}
+
+/// Whether each row of dictionary-encoded string arrays holds one of `wanted`.
+pub(crate) fn dictionary_matches<'a>(
+    arrays: impl IntoIterator<Item = &'a DictionaryStringArray>,
+    wanted: &[&str],
+) -> Vec<bool> {
+    let mut matches = Vec::new();
+    for array in arrays {
+        let wanted_keys = dictionary_wanted_keys(array, wanted);
+        matches.extend(
+            array.keys().iter().map(|key| key.is_some_and(|key| wanted_keys[key as usize])),
+        );
+    }
+    matches
+}
+
+/// Rebuilds `record_batch` with the columns of `schema`, filling the fields
+/// it lacks with nulls.
+pub(crate) fn conform_to_schema(
+    record_batch: &RecordBatch,
+    schema: SchemaRef,
+) -> Result<RecordBatch, ArrowError> {
+    let columns = schema
+        .fields()
+        .iter()
+        .map(|field| match record_batch.column_by_name(field.name()) {
+            Some(column) => column.clone(),
+            None => new_null_array(field.data_type(), record_batch.num_rows()),
+        })
+        .collect();
+    RecordBatch::try_new(schema, columns)
+}
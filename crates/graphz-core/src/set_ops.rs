@@ -0,0 +1,313 @@
+use std::collections::{HashMap, HashSet};
+
+use arrow::array::BooleanArray;
+use snafu::prelude::*;
+
+use crate::{
+    edge::EdgeRecordBatch,
+    graph::{EdgeDataSnafu, FailedToCombineGraphsSnafu, NodeDataSnafu},
+    node::NodeRecordBatch,
+    types::{NodeId, Weight},
+    Graph, GraphError, GraphKind,
+};
+
+/// How to combine the weights of edges that are merged into one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WeightMerge {
+    /// Keeps the weight of the first edge.
+    #[default]
+    First,
+    /// Keeps the weight of the last edge.
+    Last,
+    Sum,
+    Min,
+    Max,
+    Mean,
+}
+
+impl WeightMerge {
+    /// Merges `weights`, given in the order of the edges they come from.
+    pub fn merge(self, weights: &[Weight]) -> Weight {
+        let iter = weights.iter().copied();
+        match self {
+            Self::First => weights.first().copied().unwrap_or_default(),
+            Self::Last => weights.last().copied().unwrap_or_default(),
+            Self::Sum => iter.sum(),
+            Self::Min => iter.reduce(Weight::min).unwrap_or_default(),
+            Self::Max => iter.reduce(Weight::max).unwrap_or_default(),
+            Self::Mean => iter.sum::<Weight>() / weights.len().max(1) as Weight,
+        }
+    }
+}
+
+/// Ends of an edge, in either order for undirected graphs.
+pub(crate) type EdgeKey = (NodeId, NodeId);
+
+pub(crate) fn edge_key(kind: GraphKind, source_id: NodeId, target_id: NodeId) -> EdgeKey {
+    match kind {
+        GraphKind::Directed => (source_id, target_id),
+        GraphKind::Undirected => (source_id.min(target_id), source_id.max(target_id)),
+    }
+}
+
+impl Graph {
+    fn check_kind(&self, other: &Graph) -> Result<(), GraphError> {
+        match self.kind() == other.kind() {
+            true => Ok(()),
+            false => Err(GraphError::MismatchedGraphKinds),
+        }
+    }
+
+    /// Returns the ends of every edge, in row order.
+    fn edge_keys(&self) -> Result<Vec<EdgeKey>, GraphError> {
+        let kind = self.kind();
+        let sources = self.edge_data().source_ids().context(EdgeDataSnafu {})?;
+        let targets = self.edge_data().target_ids().context(EdgeDataSnafu {})?;
+        Ok(sources
+            .values()
+            .zip(targets.values())
+            .map(|(source_id, target_id)| edge_key(kind, source_id, target_id))
+            .collect())
+    }
+
+    /// Returns the weight of the first edge between each pair of ends.
+    fn edge_weights_by_key(&self) -> Result<HashMap<EdgeKey, Option<Weight>>, GraphError> {
+        let weights = self.edge_data().weights().context(EdgeDataSnafu {})?;
+        let mut weights_by_key = HashMap::with_capacity(self.num_edges());
+        for (key, weight) in self.edge_keys()?.into_iter().zip(weights.iter()) {
+            weights_by_key.entry(key).or_insert(weight);
+        }
+        Ok(weights_by_key)
+    }
+
+    /// Keeps the edges of the graph where `keep` holds for whether `other`
+    /// has an edge between the same ends. With `merge`, the weights of the
+    /// kept edges `other` has are merged with those of `other`.
+    fn own_edges(
+        &self,
+        other: &Graph,
+        keep: impl Fn(bool) -> bool,
+        merge: Option<WeightMerge>,
+    ) -> Result<EdgeRecordBatch, GraphError> {
+        let other_weights = other.edge_weights_by_key()?;
+        let edge_ids = self.edge_data().edge_ids().context(EdgeDataSnafu {})?;
+        let weights = self.edge_data().weights().context(EdgeDataSnafu {})?;
+        let mut keep_edges = Vec::with_capacity(self.num_edges());
+        let mut merged = HashMap::new();
+        for ((key, edge_id), weight) in
+            self.edge_keys()?.iter().zip(edge_ids.values()).zip(weights.iter())
+        {
+            let other_weight = other_weights.get(key);
+            let kept = keep(other_weight.is_some());
+            if let (true, Some(merge), Some(&other_weight)) = (kept, merge, other_weight) {
+                // Missing weights are left out, and stay missing if both are.
+                let weights = weight.into_iter().chain(other_weight).collect::<Vec<_>>();
+                if !weights.is_empty() {
+                    merged.insert(edge_id, merge.merge(&weights));
+                }
+            }
+            keep_edges.push(kept);
+        }
+        // Ids are taken before filtering so the ids of dropped edges are never reused.
+        let next_id = self.edge_data().next_edge_id().context(EdgeDataSnafu {})?;
+        let mut edges = self
+            .edge_data()
+            .filter(&BooleanArray::from(keep_edges))
+            .context(FailedToCombineGraphsSnafu {})?
+            .with_next_edge_id(next_id);
+        edges.set_weights(&merged).context(EdgeDataSnafu {})?;
+        Ok(edges)
+    }
+
+    /// Appends the edges of `other` between ends the graph has no edge
+    /// between, numbering them after the edges of the graph.
+    fn append_other_edges(
+        &self,
+        edges: &mut EdgeRecordBatch,
+        other: &Graph,
+    ) -> Result<(), GraphError> {
+        let own_keys = self.edge_keys()?.into_iter().collect::<HashSet<_>>();
+        let keep = other.edge_keys()?.iter().map(|key| !own_keys.contains(key)).collect::<Vec<_>>();
+        let mut other_edges = other
+            .edge_data()
+            .filter(&BooleanArray::from(keep))
+            .context(FailedToCombineGraphsSnafu {})?;
+        let next_id = self.edge_data().next_edge_id().context(EdgeDataSnafu {})?;
+        other_edges.renumber(next_id).context(FailedToCombineGraphsSnafu {})?;
+        edges.append(&other_edges).context(FailedToCombineGraphsSnafu {})
+    }
+
+    /// Returns the nodes of the graph, followed by the nodes of `other` the
+    /// graph does not have.
+    fn union_nodes(&self, other: &Graph) -> Result<NodeRecordBatch, GraphError> {
+        let other_ids = other.node_data().node_ids().context(NodeDataSnafu {})?;
+        let mut keep = Vec::with_capacity(other.num_nodes());
+        for node_id in other_ids.values() {
+            keep.push(!self.contains_node(node_id).context(NodeDataSnafu {})?);
+        }
+        let other_nodes = other
+            .node_data()
+            .filter(&BooleanArray::from(keep))
+            .context(FailedToCombineGraphsSnafu {})?;
+        let mut nodes = self.node_data().clone();
+        nodes.append(&other_nodes).context(FailedToCombineGraphsSnafu {})?;
+        Ok(nodes)
+    }
+
+    /// Returns the graph with the nodes and edges of both graphs.
+    ///
+    /// Edges are matched by their ends, and the weights of edges in both
+    /// graphs are merged by `merge`, with the weight of this graph first. The
+    /// nodes and edges of this graph keep their data and ids, and the edges
+    /// only in `other` are numbered after them.
+    pub fn union(&self, other: &Graph, merge: WeightMerge) -> Result<Graph, GraphError> {
+        self.check_kind(other)?;
+        let mut edges = self.own_edges(other, |_| true, Some(merge))?;
+        self.append_other_edges(&mut edges, other)?;
+        Graph::from_arrow_record_batches(self.union_nodes(other)?, edges)
+    }
+
+    /// Returns the graph with the nodes and edges in both graphs, with the
+    /// weights of the edges merged by `merge`.
+    pub fn intersection(&self, other: &Graph, merge: WeightMerge) -> Result<Graph, GraphError> {
+        self.check_kind(other)?;
+        let node_ids = self.node_data().node_ids().context(NodeDataSnafu {})?;
+        let mut keep = Vec::with_capacity(self.num_nodes());
+        for node_id in node_ids.values() {
+            keep.push(other.contains_node(node_id).context(NodeDataSnafu {})?);
+        }
+        let nodes = self
+            .node_data()
+            .filter(&BooleanArray::from(keep))
+            .context(FailedToCombineGraphsSnafu {})?;
+        let edges = self.own_edges(other, |shared| shared, Some(merge))?;
+        Graph::from_arrow_record_batches(nodes, edges)
+    }
+
+    /// Returns the graph with the nodes of this graph and the edges `other`
+    /// has no edge between the same ends for.
+    pub fn difference(&self, other: &Graph) -> Result<Graph, GraphError> {
+        self.check_kind(other)?;
+        let edges = self.own_edges(other, |shared| !shared, None)?;
+        Graph::from_arrow_record_batches(self.node_data().clone(), edges)
+    }
+
+    /// Returns the graph with the nodes of both graphs and the edges between
+    /// ends only one of the graphs joins.
+    pub fn symmetric_difference(&self, other: &Graph) -> Result<Graph, GraphError> {
+        self.check_kind(other)?;
+        let mut edges = self.own_edges(other, |shared| !shared, None)?;
+        self.append_other_edges(&mut edges, other)?;
+        Graph::from_arrow_record_batches(self.union_nodes(other)?, edges)
+    }
+
+    /// Returns the graph with the nodes of this graph, joining every pair of
+    /// distinct nodes the graph does not join. New edges have unit weight.
+    pub fn complement(&self) -> Result<Graph, GraphError> {
+        let kind = self.kind();
+        let joined = self.edge_keys()?.into_iter().collect::<HashSet<_>>();
+        let node_ids = self.node_data().node_ids().context(NodeDataSnafu {})?;
+        let node_ids = node_ids.values().collect::<Vec<_>>();
+        let mut sources = Vec::new();
+        let mut targets = Vec::new();
+        for (idx, &source_id) in node_ids.iter().enumerate() {
+            let first_target = match kind {
+                GraphKind::Directed => 0,
+                GraphKind::Undirected => idx + 1,
+            };
+            for &target_id in &node_ids[first_target..] {
+                if source_id != target_id && !joined.contains(&edge_key(kind, source_id, target_id))
+                {
+                    sources.push(source_id);
+                    targets.push(target_id);
+                }
+            }
+        }
+        let weights = vec![1.0; sources.len()];
+        let edges = EdgeRecordBatch::try_from_columns(sources, targets, Some(weights))
+            .context(EdgeDataSnafu {})?;
+        Graph::from_arrow_record_batches(self.node_data().clone(), edges.with_kind(kind))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{edge::Edge, node::Node};
+
+    fn build_graph(edges: &[(NodeId, NodeId, Weight)], kind: GraphKind) -> Graph {
+        let edges = edges
+            .iter()
+            .map(|&(source_id, target_id, weight)| {
+                Edge::builder()
+                    .source_id(source_id)
+                    .target_id(target_id)
+                    .weight(weight)
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+        Graph::builder().edges(edges).kind(kind).build().unwrap()
+    }
+
+    #[test]
+    fn test_union_and_intersection() {
+        let a = build_graph(&[(1, 2, 1.0), (2, 3, 2.0)], GraphKind::Directed);
+        let b = build_graph(&[(1, 2, 5.0), (3, 4, 3.0)], GraphKind::Directed);
+
+        let union = a.union(&b, WeightMerge::Sum).unwrap();
+        assert_eq!(union.num_nodes(), 4);
+        assert_eq!(union.num_edges(), 3);
+        assert_eq!(union.neighbors_with_weights(1).unwrap(), vec![(2, 6.0)]);
+        assert_eq!(union.edges_between(3, 4).unwrap(), vec![2]);
+        assert!(union.validate().unwrap().is_valid());
+
+        let intersection = a.intersection(&b, WeightMerge::Max).unwrap();
+        assert_eq!(intersection.num_nodes(), 3);
+        assert_eq!(intersection.num_edges(), 1);
+        assert_eq!(intersection.weight(0).unwrap(), Some(5.0));
+        assert_eq!(a.intersection(&b, WeightMerge::First).unwrap().weight(0).unwrap(), Some(1.0));
+
+        let undirected = build_graph(&[(2, 1, 1.0)], GraphKind::Undirected);
+        assert!(matches!(
+            a.union(&undirected, WeightMerge::First),
+            Err(GraphError::MismatchedGraphKinds)
+        ));
+        let reversed = build_graph(&[(1, 2, 4.0)], GraphKind::Undirected);
+        assert_eq!(
+            undirected.union(&reversed, WeightMerge::Mean).unwrap().weight(0).unwrap(),
+            Some(2.5)
+        );
+
+        let nodes = vec![Node::builder().id(9).key("nine").build().unwrap()];
+        let edges = vec![Edge::builder().source_id(9).target_id(9).build().unwrap()];
+        let keyed = Graph::builder().nodes(nodes).edges(edges).build().unwrap();
+        let union = a.union(&keyed, WeightMerge::First).unwrap();
+        assert_eq!(union.node_id_for_key("nine").unwrap(), Some(9));
+        assert_eq!(union.key_for_node_id(1).unwrap(), None);
+    }
+
+    #[test]
+    fn test_difference_and_complement() {
+        let a = build_graph(&[(1, 2, 1.0), (2, 3, 2.0), (3, 1, 3.0)], GraphKind::Directed);
+        let b = build_graph(&[(2, 3, 1.0), (4, 1, 1.0)], GraphKind::Directed);
+
+        let difference = a.difference(&b).unwrap();
+        assert_eq!(difference.num_nodes(), 3);
+        assert_eq!(difference.num_edges(), 2);
+        assert_eq!(difference.edges_between(3, 1).unwrap(), vec![2]);
+
+        let symmetric_difference = a.symmetric_difference(&b).unwrap();
+        assert_eq!(symmetric_difference.num_nodes(), 4);
+        assert_eq!(symmetric_difference.num_edges(), 3);
+        assert_eq!(symmetric_difference.neighbors(4).unwrap(), vec![1]);
+
+        let complement = a.complement().unwrap();
+        assert_eq!(complement.num_edges(), 3);
+        assert_eq!(complement.neighbors(1).unwrap(), vec![3]);
+        let undirected = build_graph(&[(1, 2, 1.0), (3, 2, 1.0)], GraphKind::Undirected);
+        let complement = undirected.complement().unwrap();
+        assert_eq!(complement.num_edges(), 1);
+        assert_eq!(complement.neighbors(3).unwrap(), vec![1]);
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashSet;
+
+use arrow::array::BooleanArray;
+use snafu::prelude::*;
+
+use crate::{
+    edge::{Edge, EdgeDataError},
+    graph::{EdgeDataSnafu, FailedToFilterGraphSnafu, NodeDataSnafu},
+    node::Node,
+    types::NodeId,
+    Graph, GraphError,
+};
+
+impl Graph {
+    /// Builds the graph of the rows kept by the masks, filtering each column
+    /// with the Arrow filter kernel.
+    pub(crate) fn subgraph(
+        &self,
+        keep_nodes: Vec<bool>,
+        keep_edges: Vec<bool>,
+    ) -> Result<Graph, GraphError> {
+        let node_record_batch = self
+            .node_data()
+            .filter(&BooleanArray::from(keep_nodes))
+            .context(FailedToFilterGraphSnafu {})?;
+        let edge_record_batch = self
+            .edge_data()
+            .filter(&BooleanArray::from(keep_edges))
+            .context(FailedToFilterGraphSnafu {})?;
+        Graph::from_arrow_record_batches(node_record_batch, edge_record_batch)
+    }
+
+    /// Keeps the edges whose source and target are both kept.
+    fn edges_between_kept(&self, kept: &HashSet<NodeId>) -> Result<Vec<bool>, GraphError> {
+        let source_ids = self.edge_data().source_ids().context(EdgeDataSnafu {})?;
+        let target_ids = self.edge_data().target_ids().context(EdgeDataSnafu {})?;
+        Ok(source_ids
+            .values()
+            .zip(target_ids.values())
+            .map(|(source_id, target_id)| kept.contains(&source_id) && kept.contains(&target_id))
+            .collect())
+    }
+
+    /// Returns the subgraph of `nodes` and every edge between them.
+    pub fn induced_subgraph(&self, nodes: &[NodeId]) -> Result<Graph, GraphError> {
+        for &node_id in nodes {
+            if !self.contains_node(node_id).context(NodeDataSnafu {})? {
+                return Err(GraphError::NodeNotFound { node_id });
+            }
+        }
+
+        let kept = nodes.iter().copied().collect::<HashSet<_>>();
+        let node_ids = self.node_data().node_ids().context(NodeDataSnafu {})?;
+        let keep_nodes =
+            node_ids.values().map(|node_id| kept.contains(&node_id)).collect::<Vec<_>>();
+        let keep_edges = self.edges_between_kept(&kept)?;
+        self.subgraph(keep_nodes, keep_edges)
+    }
+
+    /// Returns the subgraph of the edges at the rows `edges` and the nodes
+    /// they connect.
+    pub fn edge_subgraph(&self, edges: &[usize]) -> Result<Graph, GraphError> {
+        let mut keep_edges = vec![false; self.num_edges()];
+        let mut kept = HashSet::new();
+        for &idx in edges {
+            let Some(keep) = keep_edges.get_mut(idx) else {
+                return Err(GraphError::EdgeData { source: EdgeDataError::IndexOutOfBounds });
+            };
+            *keep = true;
+            kept.insert(self.source_id(idx).context(EdgeDataSnafu {})?);
+            kept.insert(self.target_id(idx).context(EdgeDataSnafu {})?);
+        }
+
+        let node_ids = self.node_data().node_ids().context(NodeDataSnafu {})?;
+        let keep_nodes =
+            node_ids.values().map(|node_id| kept.contains(&node_id)).collect::<Vec<_>>();
+        self.subgraph(keep_nodes, keep_edges)
+    }
+
+    /// Returns the subgraph of the nodes matching `node_predicate` and the
+    /// edges between them matching `edge_predicate`.
+    pub fn filter(
+        &self,
+        mut node_predicate: impl FnMut(&Node) -> bool,
+        mut edge_predicate: impl FnMut(&Edge) -> bool,
+    ) -> Result<Graph, GraphError> {
+        let mut kept = HashSet::new();
+        let mut keep_nodes = Vec::with_capacity(self.num_nodes());
+        for node in self.nodes_iter().context(NodeDataSnafu {})? {
+            let keep = node_predicate(&node);
+            if keep {
+                kept.insert(node.id);
+            }
+            keep_nodes.push(keep);
+        }
+
+        let edges = self.edges_iter().context(EdgeDataSnafu {})?;
+        let keep_edges = self
+            .edges_between_kept(&kept)?
+            .into_iter()
+            .zip(edges)
+            .map(|(keep, edge)| keep && edge_predicate(&edge))
+            .collect();
+        self.subgraph(keep_nodes, keep_edges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GraphKind;
+
+    fn build_graph() -> Graph {
+        let edges = vec![
+            Edge::builder().source_id(1).target_id(2).weight(1.0).build().unwrap(),
+            Edge::builder().source_id(2).target_id(3).weight(2.0).build().unwrap(),
+            Edge::builder().source_id(3).target_id(4).weight(3.0).build().unwrap(),
+            Edge::builder().source_id(4).target_id(1).weight(4.0).build().unwrap(),
+        ];
+        Graph::builder().edges(edges).kind(GraphKind::Undirected).build().unwrap()
+    }
+
+    #[test]
+    fn test_induced_subgraph() {
+        let graph = build_graph();
+        let subgraph = graph.induced_subgraph(&[1, 2, 3]).unwrap();
+        assert_eq!(subgraph.kind(), GraphKind::Undirected);
+        assert_eq!(subgraph.num_nodes(), 3);
+        assert_eq!(subgraph.num_edges(), 2);
+        assert_eq!(subgraph.neighbors(2).unwrap(), vec![1, 3]);
+        // Edge ids are kept.
+        assert_eq!(subgraph.edges_between(2, 3).unwrap(), vec![1]);
+        assert!(matches!(
+            graph.induced_subgraph(&[7]),
+            Err(GraphError::NodeNotFound { node_id: 7 })
+        ));
+    }
+
+    #[test]
+    fn test_edge_subgraph_and_filter() {
+        let graph = build_graph();
+        let subgraph = graph.edge_subgraph(&[0, 2]).unwrap();
+        assert_eq!(subgraph.num_nodes(), 4);
+        assert_eq!(subgraph.num_edges(), 2);
+        assert_eq!(subgraph.neighbors(1).unwrap(), vec![2]);
+        assert!(graph.edge_subgraph(&[4]).is_err());
+
+        let subgraph = graph
+            .filter(|node| node.id != 4, |edge| edge.weight.is_some_and(|weight| weight > 1.0))
+            .unwrap();
+        assert_eq!(subgraph.num_nodes(), 3);
+        assert_eq!(subgraph.num_edges(), 1);
+        assert_eq!(subgraph.edges_between(3, 2).unwrap(), vec![1]);
+    }
+}
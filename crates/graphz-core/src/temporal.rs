@@ -0,0 +1,297 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+use snafu::prelude::*;
+
+use crate::{
+    graph::EdgeDataSnafu,
+    types::{EdgeId, NodeId, Timestamp},
+    Graph, GraphError,
+};
+
+/// When an edge exists: at and after the event of its timestamp, within its
+/// validity interval. Missing values leave the edge unbounded in time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EdgeTime {
+    pub timestamp: Option<Timestamp>,
+    // Inclusive
+    pub valid_from: Option<Timestamp>,
+    // Exclusive
+    pub valid_to: Option<Timestamp>,
+}
+
+impl EdgeTime {
+    fn is_valid_at(&self, time: Timestamp) -> bool {
+        self.valid_from.is_none_or(|from| from <= time) && self.valid_to.is_none_or(|to| time < to)
+    }
+
+    /// Returns true if the edge's event happened by `time` and it is valid at `time`.
+    pub fn is_active_at(&self, time: Timestamp) -> bool {
+        self.timestamp.is_none_or(|timestamp| timestamp <= time) && self.is_valid_at(time)
+    }
+
+    /// Returns true if the edge's event happened within `start..end` and its
+    /// validity interval overlaps `start..end`.
+    pub fn is_active_within(&self, start: Timestamp, end: Timestamp) -> bool {
+        self.timestamp.is_none_or(|timestamp| start <= timestamp && timestamp < end)
+            && self.valid_from.is_none_or(|from| from < end)
+            && self.valid_to.is_none_or(|to| start < to)
+    }
+
+    /// Earliest time at or after `time` the edge can be followed: at its
+    /// timestamp for events, or as soon as it is valid otherwise.
+    fn earliest_departure(&self, time: Timestamp) -> Option<Timestamp> {
+        let departure = match self.timestamp {
+            Some(timestamp) => (timestamp >= time).then_some(timestamp)?,
+            None => self.valid_from.map_or(time, |from| from.max(time)),
+        };
+        self.is_valid_at(departure).then_some(departure)
+    }
+
+    /// Latest time at or before `time` the edge can be followed.
+    fn latest_departure(&self, time: Timestamp) -> Option<Timestamp> {
+        let departure = match self.timestamp {
+            Some(timestamp) => (timestamp <= time).then_some(timestamp)?,
+            // An edge valid only before Timestamp::MIN is never valid
+            None => match self.valid_to {
+                Some(to) => to.checked_sub(1)?.min(time),
+                None => time,
+            },
+        };
+        self.is_valid_at(departure).then_some(departure)
+    }
+}
+
+/// A path whose edges are followed in time order, each when it exists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemporalPath {
+    pub nodes: Vec<NodeId>,
+    pub edges: Vec<EdgeId>,
+    // Time the first edge is followed
+    pub departure: Timestamp,
+    // Time the last edge is followed
+    pub arrival: Timestamp,
+}
+
+impl Graph {
+    fn edge_times(&self) -> Result<Vec<EdgeTime>, GraphError> {
+        Ok(self.edge_data().times_iter().context(EdgeDataSnafu {})?.collect())
+    }
+
+    /// Returns the graph as it is at `time`, keeping every node and the
+    /// edges active at `time`.
+    pub fn snapshot_at(&self, time: Timestamp) -> Result<Graph, GraphError> {
+        let keep_edges = self.edge_times()?.iter().map(|times| times.is_active_at(time)).collect();
+        self.subgraph(vec![true; self.num_nodes()], keep_edges)
+    }
+
+    /// Returns the graph of the window `start..end`, keeping every node and
+    /// the edges active within it.
+    pub fn window(&self, start: Timestamp, end: Timestamp) -> Result<Graph, GraphError> {
+        let keep_edges =
+            self.edge_times()?.iter().map(|times| times.is_active_within(start, end)).collect();
+        self.subgraph(vec![true; self.num_nodes()], keep_edges)
+    }
+}
+
+/// Finds the path from `start` to `end` leaving no earlier than `departure`
+/// that arrives first. Following an edge takes no time, and each edge is
+/// followed no earlier than the one before it.
+pub fn earliest_arrival_search(
+    graph: &Graph,
+    start: NodeId,
+    end: NodeId,
+    departure: Timestamp,
+) -> Result<Option<TemporalPath>, GraphError> {
+    let index = graph.adjacency().context(EdgeDataSnafu {})?;
+    let times = graph.edge_times()?;
+    let mut arrival = HashMap::from([(start, departure)]);
+    // Previous node and edge, and the time the edge is followed
+    let mut prev: HashMap<NodeId, (NodeId, EdgeId, Timestamp)> = HashMap::new();
+    let mut heap = BinaryHeap::from([Reverse((departure, start))]);
+
+    while let Some(Reverse((time, node))) = heap.pop() {
+        if node == end {
+            let mut steps = Vec::new();
+            let mut current = end;
+            while let Some(&(previous, edge, time)) = prev.get(&current) {
+                steps.push((previous, edge, time));
+                current = previous;
+            }
+            steps.reverse();
+            return Ok(Some(path_from_steps(end, &steps, departure)));
+        }
+        if time > arrival[&node] {
+            continue;
+        }
+        let edges = index.neighbors(node).iter().zip(index.edges(node)).zip(index.edge_ids(node));
+        for ((&target, &row), &edge_id) in edges {
+            let Some(next) = times[row].earliest_departure(time) else {
+                continue;
+            };
+            if arrival.get(&target).is_none_or(|&best| next < best) {
+                arrival.insert(target, next);
+                prev.insert(target, (node, edge_id, next));
+                heap.push(Reverse((next, target)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Finds the path from `start` to `end` arriving no later than `arrival`
+/// that leaves last. Following an edge takes no time, and each edge is
+/// followed no earlier than the one before it.
+pub fn latest_departure_search(
+    graph: &Graph,
+    start: NodeId,
+    end: NodeId,
+    arrival: Timestamp,
+) -> Result<Option<TemporalPath>, GraphError> {
+    let index = graph.reverse_adjacency().context(EdgeDataSnafu {})?;
+    let times = graph.edge_times()?;
+    let mut departure = HashMap::from([(end, arrival)]);
+    // Next node and edge, and the time the edge is followed
+    let mut next: HashMap<NodeId, (NodeId, EdgeId, Timestamp)> = HashMap::new();
+    let mut heap = BinaryHeap::from([(arrival, end)]);
+
+    while let Some((time, node)) = heap.pop() {
+        if node == start {
+            let mut steps = Vec::new();
+            let mut current = start;
+            while let Some(&(following, edge, time)) = next.get(&current) {
+                steps.push((current, edge, time));
+                current = following;
+            }
+            return Ok(Some(path_from_steps(end, &steps, arrival)));
+        }
+        if time < departure[&node] {
+            continue;
+        }
+        let edges = index.neighbors(node).iter().zip(index.edges(node)).zip(index.edge_ids(node));
+        for ((&source, &row), &edge_id) in edges {
+            let Some(previous) = times[row].latest_departure(time) else {
+                continue;
+            };
+            if departure.get(&source).is_none_or(|&best| previous > best) {
+                departure.insert(source, previous);
+                next.insert(source, (node, edge_id, previous));
+                heap.push((previous, source));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Builds a path from `(node, edge, time)` steps in order, each following
+/// `edge` out of `node` at `time`. An empty path happens at `time`.
+fn path_from_steps(
+    end: NodeId,
+    steps: &[(NodeId, EdgeId, Timestamp)],
+    time: Timestamp,
+) -> TemporalPath {
+    let mut nodes = steps.iter().map(|&(node, ..)| node).collect::<Vec<_>>();
+    nodes.push(end);
+    TemporalPath {
+        nodes,
+        edges: steps.iter().map(|&(_, edge, _)| edge).collect(),
+        departure: steps.first().map_or(time, |&(.., time)| time),
+        arrival: steps.last().map_or(time, |&(.., time)| time),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge::Edge;
+
+    fn contact(source_id: NodeId, target_id: NodeId, timestamp: Timestamp) -> Edge {
+        Edge::builder()
+            .source_id(source_id)
+            .target_id(target_id)
+            .weight(1.0)
+            .timestamp(timestamp)
+            .build()
+            .unwrap()
+    }
+
+    fn build_graph() -> Graph {
+        let edges = vec![
+            contact(1, 2, 1),
+            contact(2, 3, 5),
+            contact(1, 3, 7),
+            contact(3, 4, 6),
+            contact(2, 4, 2),
+            // A link valid in 3..9
+            Edge::builder()
+                .source_id(4)
+                .target_id(5)
+                .weight(1.0)
+                .valid_from(3)
+                .valid_to(9)
+                .build()
+                .unwrap(),
+        ];
+        Graph::builder().edges(edges).build().unwrap()
+    }
+
+    #[test]
+    fn test_snapshots() {
+        let graph = build_graph();
+        assert_eq!(graph.edge_data().time(5).unwrap().valid_to, Some(9));
+        assert_eq!(graph.edge_data().edge(0).unwrap().timestamp, Some(1));
+
+        let snapshot = graph.snapshot_at(2).unwrap();
+        assert_eq!(snapshot.num_nodes(), 5);
+        assert_eq!(
+            snapshot.edges_iter().unwrap().filter_map(|edge| edge.id).collect::<Vec<_>>(),
+            vec![0, 4]
+        );
+        assert_eq!(graph.snapshot_at(6).unwrap().num_edges(), 5);
+        assert_eq!(graph.snapshot_at(9).unwrap().num_edges(), 5);
+
+        let window = graph.window(5, 7).unwrap();
+        assert_eq!(
+            window.edges_iter().unwrap().filter_map(|edge| edge.id).collect::<Vec<_>>(),
+            vec![1, 3, 5]
+        );
+        assert_eq!(graph.window(0, 3).unwrap().num_edges(), 2);
+    }
+
+    #[test]
+    fn test_time_respecting_paths() {
+        let graph = build_graph();
+        let path = earliest_arrival_search(&graph, 1, 4, 0).unwrap().unwrap();
+        assert_eq!(path.nodes, vec![1, 2, 4]);
+        assert_eq!((path.departure, path.arrival), (1, 2));
+
+        // 4->5 only becomes valid at 3
+        let path = earliest_arrival_search(&graph, 1, 5, 0).unwrap().unwrap();
+        assert_eq!(path.nodes, vec![1, 2, 4, 5]);
+        assert_eq!((path.departure, path.arrival), (1, 3));
+        assert_eq!(earliest_arrival_search(&graph, 1, 4, 2).unwrap(), None);
+
+        let path = latest_departure_search(&graph, 1, 4, 10).unwrap().unwrap();
+        assert_eq!(path.nodes, vec![1, 2, 3, 4]);
+        assert_eq!(path.edges, vec![0, 1, 3]);
+        assert_eq!((path.departure, path.arrival), (1, 6));
+        assert_eq!(latest_departure_search(&graph, 1, 4, 1).unwrap(), None);
+        assert_eq!(latest_departure_search(&graph, 2, 5, 8).unwrap().unwrap().departure, 5);
+    }
+
+    #[test]
+    fn test_valid_to_min() {
+        let edges = vec![Edge::builder()
+            .source_id(1)
+            .target_id(2)
+            .weight(1.0)
+            .valid_to(Timestamp::MIN)
+            .build()
+            .unwrap()];
+        let graph = Graph::builder().edges(edges).build().unwrap();
+        assert_eq!(latest_departure_search(&graph, 1, 2, 0).unwrap(), None);
+        assert_eq!(latest_departure_search(&graph, 1, 2, Timestamp::MIN).unwrap(), None);
+    }
+}
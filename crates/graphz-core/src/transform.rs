@@ -0,0 +1,425 @@
+use std::collections::{hash_map::Entry, BTreeMap, HashMap, HashSet};
+
+use arrow::array::BooleanArray;
+use snafu::prelude::*;
+
+use crate::{
+    edge::{Edge, EdgeRecordBatch},
+    graph::{EdgeDataSnafu, FailedToEditGraphSnafu, NodeDataSnafu},
+    graph_ref::GraphRef,
+    node::{Node, NodeRecordBatch},
+    set_ops::{edge_key, EdgeKey},
+    types::{EdgeId, NodeId, Weight},
+    Graph, GraphError, WeightMerge,
+};
+
+/// Returns the strongly connected components of the graph in topological
+/// order, so edges between components lead from earlier to later ones.
+///
+/// Undirected graphs give their connected components.
+pub fn strongly_connected_components<G: GraphRef + ?Sized>(graph: &G) -> Vec<Vec<NodeId>> {
+    let mut tarjan = Tarjan::default();
+    for root in graph.node_ids() {
+        if tarjan.indices.contains_key(&root) {
+            continue;
+        }
+        // Each frame holds a node and its remaining neighbors.
+        tarjan.visit(root);
+        let mut stack = vec![(root, graph.neighbors(root))];
+        while let Some((node_id, neighbors)) = stack.last_mut() {
+            let node_id = *node_id;
+            match neighbors.next() {
+                Some(neighbor) if !tarjan.indices.contains_key(&neighbor) => {
+                    tarjan.visit(neighbor);
+                    stack.push((neighbor, graph.neighbors(neighbor)));
+                }
+                Some(neighbor) => {
+                    if tarjan.on_stack.contains(&neighbor) {
+                        tarjan.lower(node_id, tarjan.indices[&neighbor]);
+                    }
+                }
+                None => {
+                    stack.pop();
+                    if let Some((parent, _)) = stack.last() {
+                        tarjan.lower(*parent, tarjan.low_links[&node_id]);
+                    }
+                    tarjan.finish(node_id);
+                }
+            }
+        }
+    }
+    // Tarjan's algorithm finds the components in reverse topological order.
+    tarjan.components.reverse();
+    tarjan.components
+}
+
+#[derive(Default)]
+struct Tarjan {
+    indices: HashMap<NodeId, usize>,
+    low_links: HashMap<NodeId, usize>,
+    on_stack: HashSet<NodeId>,
+    stack: Vec<NodeId>,
+    components: Vec<Vec<NodeId>>,
+}
+
+impl Tarjan {
+    fn visit(&mut self, node_id: NodeId) {
+        let index = self.indices.len();
+        self.indices.insert(node_id, index);
+        self.low_links.insert(node_id, index);
+        self.on_stack.insert(node_id);
+        self.stack.push(node_id);
+    }
+
+    fn lower(&mut self, node_id: NodeId, low_link: usize) {
+        let current = self.low_links.get_mut(&node_id).unwrap();
+        *current = (*current).min(low_link);
+    }
+
+    /// Pops the component of `node_id` once it is the root of one.
+    fn finish(&mut self, node_id: NodeId) {
+        if self.low_links[&node_id] != self.indices[&node_id] {
+            return;
+        }
+        let mut component = Vec::new();
+        while let Some(member) = self.stack.pop() {
+            self.on_stack.remove(&member);
+            component.push(member);
+            if member == node_id {
+                break;
+            }
+        }
+        component.reverse();
+        self.components.push(component);
+    }
+}
+
+impl Graph {
+    /// Returns the graph with every edge reversed. Edges keep their ids and
+    /// data.
+    pub fn transpose(&self) -> Result<Graph, GraphError> {
+        let edges = self.edge_data().reversed().context(FailedToEditGraphSnafu {})?;
+        Graph::from_arrow_record_batches(self.node_data().clone(), edges)
+    }
+
+    /// Returns the line graph, with a node per edge, taking the id and weight
+    /// of the edge. Directed graphs join each edge to the edges leaving its
+    /// target, and undirected graphs join edges once per shared end. Edges of
+    /// the line graph have unit weight.
+    pub fn line_graph(&self) -> Result<Graph, GraphError> {
+        let line_node_id = |edge_id: EdgeId| {
+            NodeId::try_from(edge_id)
+                .map_err(|_| GraphError::InvalidParameter { name: "edge id".to_string() })
+        };
+        let mut nodes = Vec::with_capacity(self.num_edges());
+        for edge in self.edges_iter().context(EdgeDataSnafu {})? {
+            let id = line_node_id(edge.id.unwrap_or_default())?;
+            nodes.push(Node { id, key: None, weight: edge.weight, position: None, label: None });
+        }
+
+        let index = self.adjacency().context(EdgeDataSnafu {})?;
+        let mut sources = Vec::new();
+        let mut targets = Vec::new();
+        if self.is_directed() {
+            let edge_ids = self.edge_data().edge_ids().context(EdgeDataSnafu {})?;
+            let target_ids = self.edge_data().target_ids().context(EdgeDataSnafu {})?;
+            for (edge_id, target_id) in edge_ids.values().zip(target_ids.values()) {
+                for &next_edge_id in index.edge_ids(target_id) {
+                    sources.push(line_node_id(edge_id)?);
+                    targets.push(line_node_id(next_edge_id)?);
+                }
+            }
+        } else {
+            let node_ids = self.node_data().node_ids().context(NodeDataSnafu {})?;
+            for node_id in node_ids.values() {
+                let edge_ids = index.edge_ids(node_id);
+                for (idx, &edge_id) in edge_ids.iter().enumerate() {
+                    for &other_edge_id in &edge_ids[idx + 1..] {
+                        sources.push(line_node_id(edge_id)?);
+                        targets.push(line_node_id(other_edge_id)?);
+                    }
+                }
+            }
+        }
+        let weights = vec![1.0; sources.len()];
+        let edges = EdgeRecordBatch::try_from_columns(sources, targets, Some(weights))
+            .context(EdgeDataSnafu {})?;
+        Graph::from_arrow_record_batches(NodeRecordBatch::from(nodes), edges.with_kind(self.kind()))
+    }
+
+    /// Returns the condensation of the graph and the component of every node.
+    ///
+    /// The condensation has a node per strongly connected component, numbered
+    /// in topological order, and joins two components by the lightest edge
+    /// between their nodes, so directed graphs condense to a DAG.
+    pub fn condensation(&self) -> Result<(Graph, HashMap<NodeId, NodeId>), GraphError> {
+        let components = strongly_connected_components(self);
+        let component_of = components
+            .iter()
+            .enumerate()
+            .flat_map(|(component, node_ids)| {
+                node_ids.iter().map(move |&node_id| (node_id, component as NodeId))
+            })
+            .collect::<HashMap<_, _>>();
+        let component = |node_id| {
+            component_of.get(&node_id).copied().ok_or(GraphError::NodeNotFound { node_id })
+        };
+
+        // Components joined only by skipped edges are joined without a weight.
+        let mut weights = BTreeMap::<EdgeKey, Option<Weight>>::new();
+        let resolved_weights = self.edge_data().resolved_weights().context(EdgeDataSnafu {})?;
+        for (edge, weight) in self.edges_iter().context(EdgeDataSnafu {})?.zip(resolved_weights) {
+            let key = edge_key(self.kind(), component(edge.source_id)?, component(edge.target_id)?);
+            if key.0 != key.1 {
+                weights
+                    .entry(key)
+                    .and_modify(|lightest| {
+                        *lightest = match (*lightest, weight) {
+                            (Some(lightest), Some(weight)) => Some(weight.min(lightest)),
+                            (lightest, weight) => lightest.or(weight),
+                        }
+                    })
+                    .or_insert(weight);
+            }
+        }
+        let edges = weights
+            .into_iter()
+            .map(|((source_id, target_id), weight)| Edge {
+                id: None,
+                source_id,
+                target_id,
+                weight,
+                timestamp: None,
+                valid_from: None,
+                valid_to: None,
+                edge_type: None,
+            })
+            .collect::<Vec<_>>();
+        let edges = EdgeRecordBatch::from(edges)
+            .with_kind(self.kind())
+            .with_missing_weight(self.edge_data().missing_weight().context(EdgeDataSnafu {})?);
+        let nodes = NodeRecordBatch::from_node_ids((0..components.len() as NodeId).collect());
+        let condensation = Graph::from_arrow_record_batches(nodes, edges)?;
+        Ok((condensation, component_of))
+    }
+
+    /// Merges `nodes` into the first of them, which keeps its data.
+    ///
+    /// Edges of the other nodes are moved to the first one, and edges joining
+    /// two of the nodes, self-loops included, are dropped. Moved edges that
+    /// end up between the same ends as another edge are merged into the first
+    /// of them, with their weights merged by `merge`. Merging a single node
+    /// leaves the graph unchanged.
+    pub fn merge_nodes(&self, nodes: &[NodeId], merge: WeightMerge) -> Result<Graph, GraphError> {
+        let Some(&kept) = nodes.first() else {
+            return Err(GraphError::InvalidParameter { name: "nodes".to_string() });
+        };
+        for &node_id in nodes {
+            if !self.contains_node(node_id).context(NodeDataSnafu {})? {
+                return Err(GraphError::NodeNotFound { node_id });
+            }
+        }
+        let merged = nodes.iter().copied().collect::<HashSet<_>>();
+        if merged.len() == 1 {
+            return Graph::from_arrow_record_batches(
+                self.node_data().clone(),
+                self.edge_data().clone(),
+            );
+        }
+        let map = |node_id| match merged.contains(&node_id) {
+            true => kept,
+            false => node_id,
+        };
+
+        let node_ids = self.node_data().node_ids().context(NodeDataSnafu {})?;
+        let keep_nodes = node_ids
+            .values()
+            .map(|node_id| node_id == kept || !merged.contains(&node_id))
+            .collect::<Vec<_>>();
+
+        // First edge, number of edges and weights of each pair of ends the
+        // moved edges end up between
+        let mut groups = HashMap::<EdgeKey, (EdgeId, usize, Vec<Weight>)>::new();
+        let mut keep_edges = Vec::with_capacity(self.num_edges());
+        let resolved_weights = self.edge_data().resolved_weights().context(EdgeDataSnafu {})?;
+        for (edge, weight) in self.edges_iter().context(EdgeDataSnafu {})?.zip(resolved_weights) {
+            let (source_id, target_id) = (edge.source_id, edge.target_id);
+            let keep = match (merged.contains(&source_id), merged.contains(&target_id)) {
+                (false, false) => true,
+                (true, true) => false,
+                _ => {
+                    let key = edge_key(self.kind(), map(source_id), map(target_id));
+                    // Skipped weights take no part in the merge.
+                    match groups.entry(key) {
+                        Entry::Vacant(entry) => {
+                            entry.insert((
+                                edge.id.unwrap_or_default(),
+                                1,
+                                weight.into_iter().collect(),
+                            ));
+                            true
+                        }
+                        Entry::Occupied(mut entry) => {
+                            let (_, num_edges, weights) = entry.get_mut();
+                            *num_edges += 1;
+                            weights.extend(weight);
+                            false
+                        }
+                    }
+                }
+            };
+            keep_edges.push(keep);
+        }
+        let weights = groups
+            .into_values()
+            .filter(|(_, num_edges, weights)| *num_edges > 1 && !weights.is_empty())
+            .map(|(edge_id, _, weights)| (edge_id, merge.merge(&weights)))
+            .collect::<HashMap<_, _>>();
+
+        let node_record_batch = self
+            .node_data()
+            .filter(&BooleanArray::from(keep_nodes))
+            .context(FailedToEditGraphSnafu {})?;
+        let edges = self.contracted_edges(keep_edges, |edges| {
+            edges.map_ends(map).context(EdgeDataSnafu {})?;
+            edges.set_weights(&weights).context(EdgeDataSnafu {})
+        })?;
+        Graph::from_arrow_record_batches(node_record_batch, edges)
+    }
+
+    /// Keeps the edges where `keep` is true, then applies `update`.
+    fn contracted_edges(
+        &self,
+        keep: Vec<bool>,
+        update: impl FnOnce(&mut EdgeRecordBatch) -> Result<(), GraphError>,
+    ) -> Result<EdgeRecordBatch, GraphError> {
+        // Ids are taken before filtering so removed edge ids are never reused.
+        let next_edge_id = self.edge_data().next_edge_id().context(EdgeDataSnafu {})?;
+        let mut edges = self
+            .edge_data()
+            .filter(&BooleanArray::from(keep))
+            .context(FailedToEditGraphSnafu {})?
+            .with_next_edge_id(next_edge_id);
+        update(&mut edges)?;
+        Ok(edges)
+    }
+
+    /// Removes the edge with id `edge_id` and merges its ends into its source,
+    /// as in [`Self::merge_nodes`].
+    pub fn contract_edge(&self, edge_id: EdgeId, merge: WeightMerge) -> Result<Graph, GraphError> {
+        let Some(row) = self.edge_row(edge_id).context(EdgeDataSnafu {})? else {
+            return Err(GraphError::EdgeNotFound { edge_id });
+        };
+        let edge = self.edge(row).context(EdgeDataSnafu {})?;
+        if edge.source_id != edge.target_id {
+            return self.merge_nodes(&[edge.source_id, edge.target_id], merge);
+        }
+        // Contracting a self-loop only removes it.
+        let keep = (0..self.num_edges()).map(|idx| idx != row).collect();
+        let edges = self.contracted_edges(keep, |_| Ok(()))?;
+        Graph::from_arrow_record_batches(self.node_data().clone(), edges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{edge::Edge, GraphKind};
+
+    fn build_graph(edges: &[(NodeId, NodeId, Weight)], kind: GraphKind) -> Graph {
+        let edges = edges
+            .iter()
+            .map(|&(source_id, target_id, weight)| {
+                Edge::builder()
+                    .source_id(source_id)
+                    .target_id(target_id)
+                    .weight(weight)
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+        Graph::builder().edges(edges).kind(kind).build().unwrap()
+    }
+
+    #[test]
+    fn test_transpose_and_line_graph() {
+        let graph = build_graph(&[(1, 2, 1.0), (2, 3, 2.0), (2, 4, 3.0)], GraphKind::Directed);
+        let transpose = graph.transpose().unwrap();
+        assert_eq!(transpose.neighbors(2).unwrap(), vec![1]);
+        assert_eq!(transpose.predecessors(2).unwrap(), vec![3, 4]);
+        assert_eq!(transpose.edges_between(4, 2).unwrap(), vec![2]);
+        assert_eq!(transpose.weight(2).unwrap(), Some(3.0));
+
+        let line_graph = graph.line_graph().unwrap();
+        assert_eq!(line_graph.num_nodes(), 3);
+        assert_eq!(line_graph.neighbors(0).unwrap(), vec![1, 2]);
+        assert!(line_graph.neighbors(1).unwrap().is_empty());
+        assert_eq!(line_graph.node_weight_by_id(2).unwrap(), Some(3.0));
+
+        let graph = build_graph(&[(1, 2, 1.0), (2, 3, 1.0), (3, 1, 1.0)], GraphKind::Undirected);
+        let line_graph = graph.line_graph().unwrap();
+        assert_eq!(line_graph.num_edges(), 3);
+        assert!(line_graph.is_complete());
+    }
+
+    #[test]
+    fn test_condensation() {
+        let graph = build_graph(
+            &[
+                (1, 2, 1.0),
+                (2, 1, 1.0),
+                (2, 3, 4.0),
+                (1, 3, 2.0),
+                (3, 4, 1.0),
+                (4, 3, 1.0),
+                (5, 1, 1.0),
+            ],
+            GraphKind::Directed,
+        );
+        let components = strongly_connected_components(&graph);
+        assert_eq!(components, vec![vec![5], vec![1, 2], vec![3, 4]]);
+
+        let (condensation, component_of) = graph.condensation().unwrap();
+        assert_eq!(condensation.num_nodes(), 3);
+        assert_eq!(component_of[&2], 1);
+        assert_eq!(condensation.neighbors_with_weights(1).unwrap(), vec![(2, 2.0)]);
+        assert_eq!(condensation.neighbors(0).unwrap(), vec![1]);
+        assert!(condensation.is_acyclic());
+    }
+
+    #[test]
+    fn test_merge_nodes_and_contract_edge() {
+        let graph = build_graph(
+            &[(1, 2, 1.0), (1, 3, 2.0), (2, 3, 3.0), (3, 4, 4.0), (2, 4, 5.0), (4, 4, 6.0)],
+            GraphKind::Directed,
+        );
+        let merged = graph.merge_nodes(&[2, 3], WeightMerge::Sum).unwrap();
+        assert_eq!(merged.num_nodes(), 3);
+        assert!(!merged.contains_node(3).unwrap());
+        assert_eq!(merged.neighbors_with_weights(1).unwrap(), vec![(2, 3.0)]);
+        assert_eq!(merged.neighbors_with_weights(2).unwrap(), vec![(4, 9.0)]);
+        assert_eq!(merged.num_edges(), 3);
+        assert!(merged.validate().unwrap().dangling_edges.is_empty());
+
+        // Self-loops on merged nodes join two of them and are dropped.
+        let merged = graph.merge_nodes(&[3, 4], WeightMerge::Sum).unwrap();
+        assert_eq!(merged.num_edges(), 3);
+        assert!(merged.edges_between(3, 3).unwrap().is_empty());
+        assert_eq!(merged.neighbors_with_weights(2).unwrap(), vec![(3, 8.0)]);
+
+        let parallel = build_graph(&[(1, 2, 1.0), (1, 2, 2.0)], GraphKind::Directed);
+        let merged = parallel.merge_nodes(&[1, 1], WeightMerge::Sum).unwrap();
+        assert_eq!(merged.num_edges(), 2);
+        assert_eq!(merged.neighbors_with_weights(1).unwrap(), vec![(2, 1.0), (2, 2.0)]);
+
+        let contracted = graph.contract_edge(2, WeightMerge::Min).unwrap();
+        assert_eq!(contracted.neighbors_with_weights(2).unwrap(), vec![(4, 4.0)]);
+        assert!(matches!(
+            graph.contract_edge(9, WeightMerge::Min),
+            Err(GraphError::EdgeNotFound { edge_id: 9 })
+        ));
+        let contracted = graph.contract_edge(5, WeightMerge::Min).unwrap();
+        assert_eq!(contracted.num_edges(), 5);
+        assert_eq!(contracted.num_nodes(), 4);
+    }
+}
@@ -1,3 +1,24 @@
+use arrow::array::PrimitiveArray;
+#[cfg(not(feature = "u64-node-ids"))]
+use arrow::datatypes::UInt32Type;
+#[cfg(feature = "u64-node-ids")]
+use arrow::datatypes::UInt64Type;
+
 pub type Weight = f64;
+pub type EdgeId = u64;
+/// Point in time of a temporal edge, in units chosen by the caller.
+pub type Timestamp = i64;
+
+// Node ids are 32-bit unless the `u64-node-ids` feature is enabled
+#[cfg(not(feature = "u64-node-ids"))]
 pub type NodeId = u32;
-pub type Position = f64;
+#[cfg(feature = "u64-node-ids")]
+pub type NodeId = u64;
+
+/// Arrow type of the node id columns, matching [`NodeId`].
+#[cfg(not(feature = "u64-node-ids"))]
+pub type NodeIdType = UInt32Type;
+#[cfg(feature = "u64-node-ids")]
+pub type NodeIdType = UInt64Type;
+
+pub type NodeIdArray = PrimitiveArray<NodeIdType>;
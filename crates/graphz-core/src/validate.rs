@@ -0,0 +1,149 @@
+use std::collections::HashSet;
+
+use arrow::array::Array;
+use snafu::prelude::*;
+
+use crate::{
+    edge::EdgeRecordBatch,
+    graph::{EdgeDataSnafu, InvalidGraphSnafu, NodeDataSnafu},
+    node::NodeRecordBatch,
+    types::{EdgeId, NodeId},
+    Graph, GraphError,
+};
+
+/// Integrity problems found by [`Graph::validate`].
+///
+/// Ids are listed in row order, and duplicate ids are listed once. Duplicate edge ids are
+/// rejected when the edge data is built, so they never show up here.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    // Edges whose source or target is not a node of the graph
+    pub dangling_edges: Vec<EdgeId>,
+    pub duplicate_node_ids: Vec<NodeId>,
+    // Self-loops are allowed, so they do not make the graph invalid
+    pub self_loops: Vec<EdgeId>,
+    // Edges with a NaN or infinite weight
+    pub non_finite_weights: Vec<EdgeId>,
+    // Required columns holding nulls
+    pub null_columns: Vec<String>,
+}
+
+impl ValidationReport {
+    /// Returns true if nothing but self-loops was found.
+    pub fn is_valid(&self) -> bool {
+        self.dangling_edges.is_empty()
+            && self.duplicate_node_ids.is_empty()
+            && self.non_finite_weights.is_empty()
+            && self.null_columns.is_empty()
+    }
+}
+
+/// Returns the values seen more than once, each listed once.
+fn duplicates<T: Copy + Eq + std::hash::Hash>(values: impl Iterator<Item = T>) -> Vec<T> {
+    let mut seen = HashSet::new();
+    let mut reported = HashSet::new();
+    values.filter(|value| !seen.insert(*value) && reported.insert(*value)).collect()
+}
+
+impl Graph {
+    /// Checks the nodes and edges for problems the schema cannot rule out.
+    pub fn validate(&self) -> Result<ValidationReport, GraphError> {
+        let node_ids = self.node_data().node_ids().context(NodeDataSnafu {})?;
+        let edge_ids = self.edge_data().edge_ids().context(EdgeDataSnafu {})?;
+        let source_ids = self.edge_data().source_ids().context(EdgeDataSnafu {})?;
+        let target_ids = self.edge_data().target_ids().context(EdgeDataSnafu {})?;
+        let weights = self.edge_data().weights().context(EdgeDataSnafu {})?;
+
+        let nodes = node_ids.values().collect::<HashSet<_>>();
+        let mut report = ValidationReport {
+            duplicate_node_ids: duplicates(node_ids.values()),
+            ..Default::default()
+        };
+        let edges = edge_ids.values().zip(source_ids.values()).zip(target_ids.values());
+        for (((edge_id, source_id), target_id), weight) in edges.zip(weights.iter()) {
+            if !nodes.contains(&source_id) || !nodes.contains(&target_id) {
+                report.dangling_edges.push(edge_id);
+            }
+            if source_id == target_id {
+                report.self_loops.push(edge_id);
+            }
+            if weight.is_some_and(|weight| !weight.is_finite()) {
+                report.non_finite_weights.push(edge_id);
+            }
+        }
+
+        let required_columns = [
+            (self.node_data().record_batches(), NodeRecordBatch::schema()),
+            (self.edge_data().record_batches(), EdgeRecordBatch::schema()),
+        ];
+        for (record_batches, schema) in required_columns {
+            for field in schema.fields().iter().filter(|field| !field.is_nullable()) {
+                let has_nulls = record_batches.iter().any(|record_batch| {
+                    record_batch
+                        .column_by_name(field.name())
+                        .is_some_and(|column| column.null_count() > 0)
+                });
+                if has_nulls {
+                    report.null_columns.push(field.name().clone());
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Like [`Graph::from_arrow_record_batches`], but also fails with
+    /// [`GraphError::InvalidGraph`] if [`Graph::validate`] finds problems.
+    pub fn from_arrow_record_batches_strict(
+        node_record_batch: NodeRecordBatch,
+        edge_record_batch: EdgeRecordBatch,
+    ) -> Result<Self, GraphError> {
+        let graph = Self::from_arrow_record_batches(node_record_batch, edge_record_batch)?;
+        let report = graph.validate()?;
+        ensure!(report.is_valid(), InvalidGraphSnafu { report: Box::new(report) });
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{edge::Edge, node::Node};
+
+    #[test]
+    fn test_validate() {
+        let nodes = vec![
+            Node::builder().id(1).build().unwrap(),
+            Node::builder().id(2).build().unwrap(),
+            Node::builder().id(2).build().unwrap(),
+        ];
+        let edges = vec![
+            Edge::builder().source_id(1).target_id(2).weight(1.0).build().unwrap(),
+            Edge::builder().source_id(2).target_id(3).weight(2.0).build().unwrap(),
+            Edge::builder().source_id(2).target_id(2).weight(f64::NAN).build().unwrap(),
+        ];
+        let graph = Graph::builder().nodes(nodes.clone()).edges(edges.clone()).build().unwrap();
+
+        let report = graph.validate().unwrap();
+        assert!(!report.is_valid());
+        assert_eq!(report.dangling_edges, vec![1]);
+        assert_eq!(report.duplicate_node_ids, vec![2]);
+        assert_eq!(report.self_loops, vec![2]);
+        assert_eq!(report.non_finite_weights, vec![2]);
+        assert!(report.null_columns.is_empty());
+
+        let result = Graph::builder().nodes(nodes).edges(edges).strict(true).build();
+        assert!(matches!(result, Err(GraphError::InvalidGraph { .. })));
+    }
+
+    #[test]
+    fn test_self_loops_are_valid() {
+        let edges = vec![
+            Edge::builder().source_id(1).target_id(2).build().unwrap(),
+            Edge::builder().source_id(2).target_id(2).build().unwrap(),
+        ];
+        let graph = Graph::builder().edges(edges).strict(true).build().unwrap();
+        let report = graph.validate().unwrap();
+        assert!(report.is_valid());
+        assert_eq!(report.self_loops, vec![1]);
+    }
+}
@@ -0,0 +1,178 @@
+use crate::{
+    graph_ref::{EdgeRef, GraphRef},
+    types::NodeId,
+};
+
+/// View of a graph with every edge reversed.
+#[derive(Debug, Clone, Copy)]
+pub struct Reversed<G>(pub G);
+
+impl<G: GraphRef> GraphRef for Reversed<G> {
+    fn is_directed(&self) -> bool {
+        self.0.is_directed()
+    }
+
+    fn node_ids(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.0.node_ids()
+    }
+
+    fn node_count(&self) -> usize {
+        self.0.node_count()
+    }
+
+    fn outgoing_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeRef> + '_ {
+        self.0.incoming_edges(node).map(EdgeRef::reversed)
+    }
+
+    fn incoming_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeRef> + '_ {
+        self.0.outgoing_edges(node).map(EdgeRef::reversed)
+    }
+}
+
+/// View of a graph with every edge followed in both directions.
+#[derive(Debug, Clone, Copy)]
+pub struct AsUndirected<G>(pub G);
+
+impl<G: GraphRef> GraphRef for AsUndirected<G> {
+    fn is_directed(&self) -> bool {
+        false
+    }
+
+    fn node_ids(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.0.node_ids()
+    }
+
+    fn node_count(&self) -> usize {
+        self.0.node_count()
+    }
+
+    fn outgoing_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeRef> + '_ {
+        // Undirected graphs already follow both directions, and self-loops
+        // are only followed once.
+        let incoming = self.0.is_directed().then(|| {
+            self.0
+                .incoming_edges(node)
+                .filter(|edge| edge.source != edge.target)
+                .map(EdgeRef::reversed)
+        });
+        self.0.outgoing_edges(node).chain(incoming.into_iter().flatten())
+    }
+
+    fn incoming_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeRef> + '_ {
+        self.outgoing_edges(node).map(EdgeRef::reversed)
+    }
+}
+
+/// View of the nodes of a graph matching a predicate, and the edges between them.
+#[derive(Debug, Clone, Copy)]
+pub struct NodeFiltered<G, F> {
+    graph: G,
+    predicate: F,
+}
+
+impl<G, F: Fn(NodeId) -> bool> NodeFiltered<G, F> {
+    pub fn new(graph: G, predicate: F) -> Self {
+        Self { graph, predicate }
+    }
+}
+
+impl<G: GraphRef, F: Fn(NodeId) -> bool> GraphRef for NodeFiltered<G, F> {
+    fn is_directed(&self) -> bool {
+        self.graph.is_directed()
+    }
+
+    fn node_ids(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.graph.node_ids().filter(|node| (self.predicate)(*node))
+    }
+
+    fn outgoing_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeRef> + '_ {
+        let kept = (self.predicate)(node);
+        self.graph.outgoing_edges(node).filter(move |edge| kept && (self.predicate)(edge.target))
+    }
+
+    fn incoming_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeRef> + '_ {
+        let kept = (self.predicate)(node);
+        self.graph.incoming_edges(node).filter(move |edge| kept && (self.predicate)(edge.source))
+    }
+}
+
+/// View of the edges of a graph matching a predicate. Every node is kept.
+#[derive(Debug, Clone, Copy)]
+pub struct EdgeFiltered<G, F> {
+    graph: G,
+    predicate: F,
+}
+
+impl<G, F: Fn(&EdgeRef) -> bool> EdgeFiltered<G, F> {
+    pub fn new(graph: G, predicate: F) -> Self {
+        Self { graph, predicate }
+    }
+}
+
+impl<G: GraphRef, F: Fn(&EdgeRef) -> bool> GraphRef for EdgeFiltered<G, F> {
+    fn is_directed(&self) -> bool {
+        self.graph.is_directed()
+    }
+
+    fn node_ids(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.graph.node_ids()
+    }
+
+    fn outgoing_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeRef> + '_ {
+        self.graph.outgoing_edges(node).filter(|edge| (self.predicate)(edge))
+    }
+
+    fn incoming_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeRef> + '_ {
+        self.graph.incoming_edges(node).filter(|edge| (self.predicate)(edge))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{breath_first_search, dijkstra_search_edges, edge::Edge, Graph, GraphType};
+
+    fn build_graph() -> Graph {
+        let edges = vec![
+            Edge::builder().source_id(1).target_id(2).weight(1.0).build().unwrap(),
+            Edge::builder().source_id(2).target_id(3).weight(2.0).build().unwrap(),
+            Edge::builder().source_id(3).target_id(4).weight(3.0).build().unwrap(),
+            Edge::builder().source_id(1).target_id(4).weight(9.0).build().unwrap(),
+        ];
+        Graph::builder().edges(edges).build().unwrap()
+    }
+
+    #[test]
+    fn test_reversed_and_undirected() {
+        let graph = build_graph();
+        assert_eq!(breath_first_search(&graph, 4, 1), None);
+        assert_eq!(breath_first_search(&Reversed(&graph), 4, 1), Some(vec![4, 1]));
+        assert_eq!(dijkstra_search_edges(&Reversed(&graph), 4, 1), Some(vec![2, 1, 0]));
+
+        assert!(graph.is_acyclic());
+        assert!(Reversed(&graph).is_acyclic());
+        assert!(!AsUndirected(&graph).is_acyclic());
+        assert!(!AsUndirected(&graph).is_directed());
+        assert_eq!(AsUndirected(&graph).neighbors(2).collect::<Vec<_>>(), vec![3, 1]);
+    }
+
+    #[test]
+    fn test_filtered() {
+        let graph = build_graph();
+        let without_3 = NodeFiltered::new(&graph, |node| node != 3);
+        assert_eq!(without_3.node_ids().count(), 3);
+        assert_eq!(dijkstra_search_edges(&without_3, 1, 4), Some(vec![3]));
+        assert!(without_3.is_connected());
+        assert!(AsUndirected(&without_3).is_acyclic());
+
+        let light = EdgeFiltered::new(&graph, |edge: &EdgeRef| {
+            edge.weight.is_some_and(|weight| weight < 5.0)
+        });
+        assert_eq!(light.neighbors(1).collect::<Vec<_>>(), vec![2]);
+        assert!(light.is_connected());
+        assert!(!EdgeFiltered::new(&graph, |edge: &EdgeRef| edge
+            .weight
+            .is_some_and(|weight| weight < 2.0))
+        .is_connected());
+    }
+}
@@ -1,7 +1,17 @@
-use arrow::{error::ArrowError, ipc::reader::FileReader};
-use graphz_core::{edge::EdgeRecordBatch, node::NodeRecordBatch, Graph};
+use arrow::{
+    array::RecordBatch,
+    compute::{cast_with_options, CastOptions},
+    datatypes::{ArrowPrimitiveType, Field, Schema, SchemaRef},
+    error::ArrowError,
+    ipc::reader::FileReader,
+};
+use graphz_core::{
+    edge::{self, EdgeRecordBatch},
+    node::{self, NodeRecordBatch},
+    Graph, NodeIdType,
+};
 use snafu::prelude::*;
-use std::{fs::File, path::PathBuf};
+use std::{fs::File, path::PathBuf, sync::Arc};
 
 #[derive(Debug, Snafu)]
 pub enum ReadGraphError {
@@ -11,55 +21,184 @@ pub enum ReadGraphError {
 }
 type Result<T, E = ReadGraphError> = std::result::Result<T, E>;
 
+/// Reads the schema and all record batches of an Arrow IPC file. Each batch
+/// is kept as a chunk of the graph.
+fn read_record_batches(path: PathBuf) -> Result<(SchemaRef, Vec<RecordBatch>)> {
+    let file =
+        File::open(path).map_err(|source| ReadGraphError::ArrowError { source: source.into() })?;
+    let reader = FileReader::try_new(file, None).context(ArrowSnafu {})?;
+    let schema = reader.schema();
+    let record_batches = reader.collect::<Result<Vec<_>, _>>().context(ArrowSnafu {})?;
+    Ok((schema, record_batches))
+}
+
+/// Casts the integer node id columns `names` to the width of [`NodeIdType`],
+/// so files written with 32-bit or 64-bit node ids can be read by either build.
+fn cast_node_ids(
+    (schema, record_batches): (SchemaRef, Vec<RecordBatch>),
+    names: &[&str],
+) -> Result<(SchemaRef, Vec<RecordBatch>)> {
+    let options = CastOptions { safe: false, ..Default::default() };
+    let is_cast = |field: &Field| {
+        names.contains(&field.name().as_str())
+            && field.data_type().is_integer()
+            && field.data_type() != &NodeIdType::DATA_TYPE
+    };
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|field| match is_cast(field) {
+            true => Arc::new(field.as_ref().clone().with_data_type(NodeIdType::DATA_TYPE)),
+            false => field.clone(),
+        })
+        .collect::<Vec<_>>();
+    let cast_schema = Arc::new(Schema::new_with_metadata(fields, schema.metadata().clone()));
+
+    let mut cast_record_batches = Vec::with_capacity(record_batches.len());
+    for record_batch in record_batches {
+        let mut columns = Vec::with_capacity(schema.fields().len());
+        for (field, column) in schema.fields().iter().zip(record_batch.columns()) {
+            match is_cast(field) {
+                true => columns.push(
+                    cast_with_options(column, &NodeIdType::DATA_TYPE, &options)
+                        .context(ArrowSnafu {})?,
+                ),
+                false => columns.push(column.clone()),
+            }
+        }
+        cast_record_batches
+            .push(RecordBatch::try_new(cast_schema.clone(), columns).context(ArrowSnafu {})?);
+    }
+    Ok((cast_schema, cast_record_batches))
+}
+
 pub fn read_graph_from_arrow_files(path: &str) -> Result<Graph> {
     let target = PathBuf::from(path);
-    let mut files = target.read_dir().map_err(|_| ReadGraphError::FileNotFound)?;
-    let nodes_path = files.find_map(|file| {
-        if file.is_ok()
-            && file.as_ref().unwrap().path().is_file()
-            && file.as_ref().unwrap().path().to_str().unwrap().contains("graph.nodes.arrow")
-        {
-            Some(file.unwrap().path())
-        } else {
-            None
-        }
-    });
-    let edges_path = files.find_map(|file| {
-        if file.is_ok()
-            && file.as_ref().unwrap().path().is_file()
-            && file.as_ref().unwrap().path().to_str().unwrap().contains("graph.edges.arrow")
-        {
-            Some(file.unwrap().path())
-        } else {
-            None
-        }
-    });
-    if nodes_path.is_none() || edges_path.is_none() {
+    let files = target
+        .read_dir()
+        .map_err(|_| ReadGraphError::FileNotFound)?
+        .filter_map(|file| file.ok().map(|file| file.path()))
+        .filter(|path| path.is_file())
+        .collect::<Vec<_>>();
+    let find_file = |name: &str| {
+        files.iter().find(|path| path.to_str().is_some_and(|path| path.contains(name))).cloned()
+    };
+    let (Some(nodes_path), Some(edges_path)) =
+        (find_file("graph.nodes.arrow"), find_file("graph.edges.arrow"))
+    else {
         return Err(ReadGraphError::FileNotFound);
-    }
-    let nodes_path = nodes_path.unwrap();
-    let edges_path = edges_path.unwrap();
+    };
 
-    let mut node_record_batch = FileReader::try_new(
-        File::open(nodes_path)
-            .map_err(|source| ReadGraphError::ArrowError { source: source.into() })?,
-        None,
-    )
-    .map_err(|source| ReadGraphError::ArrowError { source })?;
-    let node_record_batch = node_record_batch.next().unwrap().unwrap();
-    let mut edges_record_batch = FileReader::try_new(
-        File::open(edges_path)
-            .map_err(|source| ReadGraphError::ArrowError { source: source.into() })?,
-        None,
-    )
-    .map_err(|source| ReadGraphError::ArrowError { source })?;
-    let edges_record_batch = edges_record_batch.next().unwrap().unwrap();
+    let (node_schema, node_record_batches) =
+        cast_node_ids(read_record_batches(nodes_path)?, &[node::Attribute::Node.as_ref()])?;
+    let (edge_schema, edge_record_batches) = cast_node_ids(
+        read_record_batches(edges_path)?,
+        &[edge::Attribute::Source.as_ref(), edge::Attribute::Target.as_ref()],
+    )?;
 
-    let graph = Graph::from_arrow_record_batches(
-        NodeRecordBatch::from(node_record_batch),
-        EdgeRecordBatch::from(edges_record_batch),
-    )
-    .map_err(|_| ReadGraphError::InvalidGraphFormat)?;
+    let node_record_batch =
+        NodeRecordBatch::try_from_record_batches(node_schema, node_record_batches)
+            .map_err(|_| ReadGraphError::InvalidGraphFormat)?;
+    let edge_record_batch =
+        EdgeRecordBatch::try_from_record_batches(edge_schema, edge_record_batches)
+            .map_err(|_| ReadGraphError::InvalidGraphFormat)?;
+    let graph = Graph::from_arrow_record_batches(node_record_batch, edge_record_batch)
+        .map_err(|_| ReadGraphError::InvalidGraphFormat)?;
 
     Ok(graph)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::writer::write_graph_to_arrow_files;
+    use arrow::{array::StringArray, compute::cast, datatypes::DataType, ipc::writer::FileWriter};
+    use graphz_core::{edge::Edge, node::Node, GraphKind, MissingWeight};
+
+    #[test]
+    fn test_round_trip() {
+        let node_record_batch = NodeRecordBatch::from(vec![
+            Node::builder().id(1).position((1.0, 2.0)).build().unwrap(),
+            Node::builder().id(2).build().unwrap(),
+        ]);
+        let record_batch = node_record_batch.to_record_batch();
+        let mut fields = record_batch.schema().fields().to_vec();
+        fields.push(Arc::new(Field::new("label", DataType::Utf8, true)));
+        let mut columns = record_batch.columns().to_vec();
+        columns.push(Arc::new(StringArray::from(vec![Some("user"), None])));
+        let node_record_batch =
+            RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap();
+        let edge_record_batch =
+            EdgeRecordBatch::from(vec![Edge::builder().source_id(1).target_id(2).build().unwrap()])
+                .with_kind(GraphKind::Undirected)
+                .with_missing_weight(MissingWeight::Skip);
+        let graph = Graph::from_arrow_record_batches(
+            NodeRecordBatch::from(node_record_batch),
+            edge_record_batch,
+        )
+        .unwrap();
+
+        let path = std::env::temp_dir().join("graphz-io-test-round-trip");
+        write_graph_to_arrow_files(&graph, path.to_str().unwrap()).unwrap();
+        let read_graph = read_graph_from_arrow_files(path.to_str().unwrap()).unwrap();
+
+        assert_eq!(read_graph.kind(), GraphKind::Undirected);
+        assert_eq!(read_graph.node_record_batch(), graph.node_record_batch());
+        assert_eq!(read_graph.edge_record_batch(), graph.edge_record_batch());
+        assert_eq!(read_graph.node_property::<String>(0, "label").unwrap(), Some("user".into()));
+        assert_eq!(read_graph.missing_weight().unwrap(), MissingWeight::Skip);
+        assert_eq!(read_graph.weight(0).unwrap(), None);
+        assert_eq!(read_graph.node_weight_by_id(2).unwrap(), None);
+    }
+
+    #[test]
+    fn test_read_other_node_id_width() {
+        let graph = Graph::builder()
+            .edges(vec![Edge::builder().source_id(1).target_id(2).build().unwrap()])
+            .build()
+            .unwrap();
+        let other_data_type = match NodeIdType::DATA_TYPE {
+            DataType::UInt32 => DataType::UInt64,
+            _ => DataType::UInt32,
+        };
+
+        let path = std::env::temp_dir().join("graphz-io-test-node-id-width");
+        std::fs::create_dir_all(&path).unwrap();
+        for (name, record_batch, columns) in [
+            ("graph.nodes.arrow", graph.node_record_batch(), vec!["node"]),
+            ("graph.edges.arrow", graph.edge_record_batch(), vec!["source", "target"]),
+        ] {
+            let schema = record_batch.schema();
+            let fields = schema.fields().iter().map(|field| {
+                match columns.contains(&field.name().as_str()) {
+                    true => {
+                        Arc::new(field.as_ref().clone().with_data_type(other_data_type.clone()))
+                    }
+                    false => field.clone(),
+                }
+            });
+            let schema = Arc::new(Schema::new_with_metadata(
+                fields.collect::<Vec<_>>(),
+                schema.metadata().clone(),
+            ));
+            let record_batch = RecordBatch::try_new(
+                schema.clone(),
+                record_batch
+                    .columns()
+                    .iter()
+                    .zip(schema.fields())
+                    .map(|(column, field)| cast(column, field.data_type()).unwrap())
+                    .collect(),
+            )
+            .unwrap();
+            let mut writer =
+                FileWriter::try_new(File::create(path.join(name)).unwrap(), &schema).unwrap();
+            writer.write(&record_batch).unwrap();
+            writer.finish().unwrap();
+        }
+
+        let read_graph = read_graph_from_arrow_files(path.to_str().unwrap()).unwrap();
+        assert_eq!(read_graph.node_record_batch(), graph.node_record_batch());
+        assert_eq!(read_graph.edge_record_batch(), graph.edge_record_batch());
+    }
+}
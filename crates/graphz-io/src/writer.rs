@@ -1,5 +1,5 @@
 use arrow::ipc::writer::FileWriter;
-use graphz_core::{edge::EdgeRecordBatch, node::NodeRecordBatch, Graph};
+use graphz_core::Graph;
 use snafu::prelude::*;
 use std::{
     fs::{create_dir_all, File},
@@ -25,14 +25,20 @@ pub fn write_graph_to_arrow_files(graph: &Graph, path: &str) -> Result<()> {
     let mut edges_path = PathBuf::from(path);
     edges_path.push(format!("graph.edges.arrow"));
 
-    let schema = NodeRecordBatch::schema();
-    let mut writer = FileWriter::try_new(File::create(nodes_path).unwrap(), &schema).unwrap();
-    writer.write(&graph.node_record_batch()).unwrap();
+    // Each chunk of the graph is written as its own record batch.
+    let node_schema = graph.node_schema();
+    let mut writer = FileWriter::try_new(File::create(nodes_path).unwrap(), &node_schema).unwrap();
+    for record_batch in graph.node_record_batches() {
+        writer.write(record_batch).unwrap();
+    }
     writer.finish().unwrap();
 
-    let schema = EdgeRecordBatch::schema();
-    let mut writer = FileWriter::try_new(File::create(edges_path).unwrap(), &schema).unwrap();
-    writer.write(&graph.edge_record_batch()).unwrap();
+    // The edge schema metadata carries the graph kind.
+    let edge_schema = graph.edge_schema();
+    let mut writer = FileWriter::try_new(File::create(edges_path).unwrap(), &edge_schema).unwrap();
+    for record_batch in graph.edge_record_batches() {
+        writer.write(record_batch).unwrap();
+    }
     writer.finish().unwrap();
 
     Ok(())
@@ -1,4 +1,4 @@
-use graphz_core::{breath_first_search, edge::Edge, Graph};
+use graphz_core::{breath_first_search, generators, GraphKind, NodeId};
 use snafu::Whatever;
 
 fn build_big_graph() -> Result<(), Whatever> {
@@ -10,22 +10,14 @@ fn build_big_graph() -> Result<(), Whatever> {
     let num_nodes = 1000;
     let num_edges = num_nodes * 5;
 
-    let mut edges = Vec::with_capacity(num_edges);
-    // Add edges
-    for _ in 0..num_edges {
-        let src = rand::random_range(0..num_nodes);
-        let dst = rand::random_range(0..num_nodes);
-        edges.push(Edge::builder().source_id(src as u32).target_id(dst as u32).build().unwrap());
-    }
-    println!("Edges created: {}", edges.len());
-
-    // Build the graph
-    let g = Graph::builder().edges(edges).build().unwrap();
+    // Build a random graph
+    let g = generators::gnm(num_nodes, num_edges, GraphKind::Directed, &mut rand::rng()).unwrap();
+    println!("Edges created: {}", g.num_edges());
     assert_eq!(g.num_nodes(), num_nodes);
     assert_eq!(g.num_edges(), num_edges);
 
-    let start = rand::random_range(0..num_nodes) as u32;
-    let end = rand::random_range(0..num_nodes) as u32;
+    let start = rand::random_range(0..num_nodes) as NodeId;
+    let end = rand::random_range(0..num_nodes) as NodeId;
 
     println!("Finding path from {} to {}", start, end);
     let path = breath_first_search(&g, start, end);